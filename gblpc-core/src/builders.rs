@@ -0,0 +1,841 @@
+use ron::{extensions::Extensions, options::Options};
+use serde::{Serialize, Deserialize};
+use serde_inline_default::serde_inline_default;
+use std::collections::HashMap;
+use thiserror::Error;
+use crate::game::*;
+use crate::rules::*;
+use crate::link::PuzzLink;
+use crate::puzzle::dsl::{CellContent, PuzzleType};
+
+/// How catalogues are written: no struct names, and `Some` left implicit
+pub fn ron_options() -> Options {
+    Options::default()
+        .without_default_extension(Extensions::EXPLICIT_STRUCT_NAMES)
+        .with_default_extension(Extensions::IMPLICIT_SOME)
+}
+
+/// Reads every game in a `*.games.ron` catalogue, skipping how their cells look
+pub fn parse_games(source: &str) -> Result<Vec<GameBuilder>, ron::error::SpannedError> {
+    ron_options().from_str(source)
+}
+
+/// Why a game can't be built, caught before it could index out of bounds or divide by zero
+#[derive(Debug, Error)]
+pub enum BuildError {
+    #[error("board is {0}x{1}, but needs at least one cell")]
+    EmptyBoard(usize, usize),
+    #[error("cell `{cell}` refers to unknown cell `{target}`")]
+    UnknownCell { cell: String, target: String },
+    #[error("cell `{cell}` has an empty `{key}` list")]
+    EmptyList { cell: String, key: String },
+    #[error("rule refers to unknown cell `{0}`")]
+    UnknownRuleCell(String),
+    #[error("rule refers to ({0}, {1}), which is outside the board")]
+    CellOutOfBounds(usize, usize),
+    #[error("{block_width}x{block_height} blocks don't tile the {width}x{height} board")]
+    UntiledBlocks { block_width: usize, block_height: usize, width: usize, height: usize },
+    #[error("region map is {map_width}x{map_height}, but the board is {width}x{height}")]
+    RegionMapSize { map_width: usize, map_height: usize, width: usize, height: usize },
+    #[error("rule is checked in `Regions`, but the game has no `regions`")]
+    NoRegions,
+    #[error("track refers to unknown cell `{0}`")]
+    UnknownTrackCell(String),
+    #[error("puzz.link format refers to unknown cell `{0}`")]
+    UnknownLinkCell(String),
+    #[error("puzz.link format can't generate {0:?} puzzles, only sudokus")]
+    NoLinkGenerator(PuzzleType),
+    #[error("puzz.link format generates sudokus, but lists no cell for {0}")]
+    NoLinkDigit(u8),
+    #[error("{side:?} track has {found} numbers, but the board has {lines} lines along that side")]
+    TrackLength { side: Side, found: usize, lines: usize },
+    #[error("cell `{0}` is defined more than once")]
+    DuplicateCell(String),
+    #[error("cell `{0}` is a second `Default` cell")]
+    MultipleDefaults(String),
+    #[error("cell `{cell}` has probability {prob}, which is outside 0..=1")]
+    ProbabilityOutOfRange { cell: String, prob: f64 },
+    #[error("cell `{cell}` asks for {count} cells, but the board only has {cells}")]
+    CountExceedsBoard { cell: String, count: usize, cells: usize },
+    #[error("cell `{cell}` asks for {count} mines, but only {room} cells lie outside the first click's 3x3 opening")]
+    NoGuessExceedsBoard { cell: String, count: usize, room: usize },
+    #[error("cell `{cell}` has an empty count range {min}..={max}")]
+    EmptyCountRange { cell: String, min: usize, max: usize },
+    #[error("random probabilities sum to {0}, which is more than 1")]
+    ProbabilitiesExceedOne(f64),
+    #[error("random probabilities leave {0} unassigned but no cell is `Default`")]
+    MissingDefault(f64),
+}
+
+/// A piece of RON text to search for when locating an error in the source
+#[derive(Clone, Copy, Debug)]
+pub enum Anchor<'a> {
+    /// `key: "value"`
+    Value(&'a str, &'a str),
+    /// `key:`
+    Key(&'a str),
+    /// `"value"` anywhere
+    Quoted(&'a str),
+}
+
+/// A `BuildError` and the text to search for, in order, to find where in the catalogue it was made
+#[derive(Debug)]
+pub struct Invalid<'a> {
+    pub anchors: Vec<Anchor<'a>>,
+    pub cell: Option<&'a str>,
+    pub error: BuildError,
+}
+impl<'a> Invalid<'a> {
+    fn at(anchors: &[Anchor<'a>], cell: Option<&'a str>, error: BuildError) -> Self {
+        Self { anchors: anchors.to_vec(), cell, error }
+    }
+}
+
+/// A catalogue game as far as the engine goes, e.g. for the web API, which has no use for styles or colors
+#[serde_inline_default]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GameBuilder {
+    pub name: String,
+    #[serde_inline_default(10)]
+    pub width: usize,
+    #[serde_inline_default(10)]
+    pub height: usize,
+    pub noun_builder: NounBuilder,
+    #[serde(default)]
+    pub rules: Vec<RuleBuilder>,
+    #[serde(default)]
+    pub regions: RegionsBuilder,
+    #[serde(default)]
+    pub tracks: Vec<TrackBuilder>,
+    #[serde(default)]
+    pub puzz_link: Option<PuzzLinkBuilder>,
+}
+impl GameBuilder {
+    /// Every schema and reference error in this game, instead of stopping at the first one
+    pub fn validate(&self) -> Vec<Invalid<'_>> {
+        let mut errors = Vec::new();
+        let game = Anchor::Value("name", &self.name);
+        let cells = &self.noun_builder.spatial_element_builder.cells;
+
+        if self.width == 0 || self.height == 0 {
+            let key = if self.width == 0 { "width" } else { "height" };
+            errors.push(Invalid::at(&[game, Anchor::Key(key)], None, BuildError::EmptyBoard(self.width, self.height)));
+        } else if let Err(error) = self.regions.check(self.width, self.height) {
+            errors.push(Invalid::at(&[game, Anchor::Key("regions")], None, error));
+        }
+
+        let mut names = HashMap::new();
+        for cell in cells.iter() {
+            let count = names.entry(cell.name.as_str()).or_insert(0);
+            *count += 1;
+            if *count == 2 {
+                let anchors = [game, Anchor::Value("name", &cell.name), Anchor::Value("name", &cell.name)];
+                errors.push(Invalid::at(&anchors, Some(&cell.name), BuildError::DuplicateCell(cell.name.clone())));
+            }
+        }
+
+        let board = self.width * self.height;
+        let mut defaults = 0;
+        let mut prob_sum = 0.0;
+        for cell in cells.iter() {
+            let at = |anchor, error| Invalid::at(&[game, Anchor::Value("name", &cell.name), anchor], Some(&cell.name), error);
+            let mut targets = Vec::new();
+            for (key, action) in [("l_click", &cell.l_click), ("r_click", &cell.r_click)] {
+                let Some(action) = action else { continue };
+                targets.extend(action.targets());
+                if let Some(list) = action.empty_list() {
+                    errors.push(at(Anchor::Key(key), BuildError::EmptyList { cell: cell.name.clone(), key: list.to_string() }));
+                }
+            }
+            if let GenMethodBuilder::DependOn { on, .. } = &cell.gen_method {
+                targets.push(on);
+            }
+            if let Some(forbid_adjacent) = &cell.forbid_adjacent {
+                targets.extend(forbid_adjacent.nouns.iter().map(String::as_str));
+            }
+            targets.extend(cell.counts_as.as_deref());
+            if let Some(reveal) = &cell.reveal {
+                targets.extend(reveal.count.iter().chain(reveal.into.iter()).map(String::as_str));
+                if reveal.into.is_empty() {
+                    errors.push(at(Anchor::Key("reveal"), BuildError::EmptyList { cell: cell.name.clone(), key: "into".to_string() }));
+                }
+            }
+            for target in targets {
+                if !names.contains_key(target) {
+                    errors.push(at(Anchor::Quoted(target), BuildError::UnknownCell { cell: cell.name.clone(), target: target.to_string() }));
+                }
+            }
+            match cell.gen_method {
+                GenMethodBuilder::None => (),
+                GenMethodBuilder::Default => {
+                    defaults += 1;
+                    if defaults == 2 {
+                        errors.push(at(Anchor::Key("gen_method"), BuildError::MultipleDefaults(cell.name.clone())));
+                    }
+                },
+                GenMethodBuilder::Random(prob) => {
+                    if !(0.0..=1.0).contains(&prob) {
+                        errors.push(at(Anchor::Key("gen_method"), BuildError::ProbabilityOutOfRange { cell: cell.name.clone(), prob }));
+                    }
+                    prob_sum += prob;
+                },
+                GenMethodBuilder::NoGuess { count, .. } if count > board.saturating_sub(9) => {
+                    let room = board.saturating_sub(9);
+                    errors.push(at(Anchor::Key("gen_method"), BuildError::NoGuessExceedsBoard { cell: cell.name.clone(), count, room }));
+                },
+                GenMethodBuilder::Count(count) | GenMethodBuilder::CountRange(_, count) if count > board => {
+                    errors.push(at(Anchor::Key("gen_method"), BuildError::CountExceedsBoard { cell: cell.name.clone(), count, cells: board }));
+                },
+                GenMethodBuilder::CountRange(min, max) if min > max => {
+                    errors.push(at(Anchor::Key("gen_method"), BuildError::EmptyCountRange { cell: cell.name.clone(), min, max }));
+                },
+                GenMethodBuilder::Count(_) | GenMethodBuilder::CountRange(..) | GenMethodBuilder::DependOn { .. } | GenMethodBuilder::NoGuess { .. } => (),
+            }
+        }
+
+        for rule in self.rules.iter() {
+            for target in rule.targets() {
+                if !names.contains_key(target) {
+                    errors.push(Invalid::at(&[game, Anchor::Key("rules"), Anchor::Quoted(target)], None, BuildError::UnknownRuleCell(target.to_string())));
+                }
+            }
+            for (x, y) in rule.region_cells() {
+                if x >= self.width || y >= self.height {
+                    errors.push(Invalid::at(&[game, Anchor::Key("rules")], None, BuildError::CellOutOfBounds(x, y)));
+                }
+            }
+            if rule.uses_regions() && matches!(self.regions, RegionsBuilder::None) {
+                errors.push(Invalid::at(&[game, Anchor::Key("rules")], None, BuildError::NoRegions));
+            }
+        }
+
+        for track in self.tracks.iter() {
+            for target in track.targets() {
+                if !names.contains_key(target) {
+                    errors.push(Invalid::at(&[game, Anchor::Key("tracks"), Anchor::Quoted(target)], None, BuildError::UnknownTrackCell(target.to_string())));
+                }
+            }
+            if let Err(error) = track.check(self.width, self.height) {
+                errors.push(Invalid::at(&[game, Anchor::Key("tracks")], None, error));
+            }
+        }
+
+        if let Some(puzz_link) = &self.puzz_link {
+            for (target, _) in puzz_link.cells.iter() {
+                if !names.contains_key(target.as_str()) {
+                    errors.push(Invalid::at(&[game, Anchor::Key("puzz_link"), Anchor::Quoted(target)], None, BuildError::UnknownLinkCell(target.clone())));
+                }
+            }
+            if puzz_link.generate && puzz_link.puzzle != PuzzleType::Sudoku {
+                errors.push(Invalid::at(&[game, Anchor::Key("puzz_link"), Anchor::Key("generate")], None, BuildError::NoLinkGenerator(puzz_link.puzzle.clone())));
+            } else if puzz_link.generate {
+                let mut digits = (1..=self.width).filter_map(|digit| u8::try_from(digit).ok());
+                if let Some(digit) = digits.find(|&digit| !puzz_link.cells.iter().any(|(_, content)| *content == CellContent::Number(digit))) {
+                    errors.push(Invalid::at(&[game, Anchor::Key("puzz_link")], None, BuildError::NoLinkDigit(digit)));
+                }
+            }
+        }
+
+        if prob_sum > 1.0 + f64::EPSILON {
+            errors.push(Invalid::at(&[game], None, BuildError::ProbabilitiesExceedOne(prob_sum)));
+        } else if defaults == 0 && prob_sum < 1.0 - f64::EPSILON {
+            errors.push(Invalid::at(&[game], None, BuildError::MissingDefault(1.0 - prob_sum)));
+        }
+        errors
+    }
+
+    /// The first error `validate` finds, any of which would stop `to_game`
+    pub fn check(&self) -> Result<(), BuildError> {
+        match self.validate().into_iter().next() {
+            Some(invalid) => Err(invalid.error),
+            None => Ok(()),
+        }
+    }
+
+    pub fn to_game(self) -> Result<Game, BuildError> {
+        self.check()?;
+        let Self { name, width, height, noun_builder, rules, regions, tracks, puzz_link } = self;
+        let nouns = to_nouns(noun_builder.spatial_element_builder.cells);
+        let regions = regions.to_regions(width, height);
+        Ok(Game {
+            name,
+            width,
+            height,
+            rules: rules.into_iter().map(|rule| rule.to_rule(&nouns.noun_map, &regions)).collect(),
+            tracks: tracks.into_iter().map(|track| track.to_track(&nouns.noun_map)).collect(),
+            puzz_link: puzz_link.map(|puzz_link| puzz_link.to_puzz_link(&nouns.noun_map)),
+            nouns,
+            regions,
+            clue_rules: Vec::new(),
+        })
+    }
+}
+
+/// The puzz.link puzzle type a game plays, and the given each listed cell stands for; the first cell listed for a
+/// given is the one a link lays out
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PuzzLinkBuilder {
+    pub puzzle: PuzzleType,
+    /// Deal the givens of fresh boards from the puzzle generator rather than the cells' `gen_method`s
+    #[serde(default)]
+    pub generate: bool,
+    pub cells: Vec<(String, CellContent)>,
+}
+impl PuzzLinkBuilder {
+    pub fn to_puzz_link(self, noun_map: &HashMap<String, usize>) -> PuzzLink {
+        PuzzLink {
+            puzzle_type: self.puzzle,
+            generate: self.generate,
+            cells: self.cells.into_iter().map(|(name, content)| (noun_map[&name], content)).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct NounBuilder {
+    pub spatial_element_builder: SpatialElementBuilder,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct SpatialElementBuilder {
+    pub cells: Vec<CellBuilder>,
+}
+
+/// What a catalogue cell does; the game crate reads the same entry again for how it looks
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct CellBuilder {
+    pub name: String,
+    pub l_click: Option<ActionBuilder>,
+    pub r_click: Option<ActionBuilder>,
+    pub reveal: Option<RevealBuilder>,
+    pub gen_method: GenMethodBuilder,
+    pub forbid_adjacent: Option<ForbidAdjacentBuilder>,
+    pub blank: bool,
+    pub counts_as: Option<String>,
+}
+impl CellBuilder {
+    pub fn to_noun(self, noun_map: &HashMap<String, usize>) -> Noun {
+        let Self { name, l_click, r_click, reveal, blank, counts_as, .. } = self;
+        Noun::SpatialElement(
+            SpatialElement::Cell {
+                name,
+                l_click: l_click.map(|action| action.to_action(noun_map)),
+                r_click: r_click.map(|action| action.to_action(noun_map)),
+                reveal: reveal.map(|reveal| reveal.to_reveal(noun_map)),
+                blank,
+                counts_as: counts_as.map(|noun| noun_map[&noun]),
+            }
+        )
+    }
+}
+
+/// Numbers the cells in order and gathers how each one is generated
+pub fn to_nouns(cells: Vec<CellBuilder>) -> Nouns {
+    let mut noun_map = HashMap::new();
+    let mut gen_config = GenConfig::default();
+    for (noun_id, cell) in cells.iter().enumerate() {
+        noun_map.insert(cell.name.clone(), noun_id);
+    }
+    for (noun_id, cell) in cells.iter().enumerate() {
+        match cell.gen_method {
+            GenMethodBuilder::None => (),
+            GenMethodBuilder::Default => gen_config.default = noun_id,
+            GenMethodBuilder::Random(mut prob) => {
+                if let Some((last_prob, _)) = gen_config.probs.last() {
+                    prob += last_prob;
+                }
+                gen_config.probs.push((prob, noun_id));
+            },
+            GenMethodBuilder::Count(count) => gen_config.counts.push((noun_id, count, count)),
+            GenMethodBuilder::CountRange(min, max) => gen_config.counts.push((noun_id, min, max)),
+            GenMethodBuilder::DependOn { ref on, neighborhood } => gen_config.depends.push((noun_id, noun_map[on], neighborhood)),
+            GenMethodBuilder::NoGuess { count, attempts } => gen_config.no_guess = Some(NoGuess { noun: noun_id, count, attempts }),
+        }
+        if let Some(ForbidAdjacentBuilder { nouns, neighborhood }) = &cell.forbid_adjacent {
+            let nouns = nouns.iter().map(|name| noun_map[name]).collect();
+            gen_config.forbids.insert(noun_id, (nouns, *neighborhood));
+        }
+    }
+    let nouns = cells.into_iter().map(|cell| cell.to_noun(&noun_map)).collect();
+    Nouns {
+        nouns,
+        noun_map,
+        gen_config,
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub enum GenMethodBuilder {
+    #[default]
+    None,
+    Default,
+    Random(f64),
+    /// Exactly this many cells
+    Count(usize),
+    CountRange(usize, usize),
+    /// One cell next to every cell of `on`, where there's room
+    DependOn {
+        on: String,
+        #[serde(default)]
+        neighborhood: Neighborhood,
+    },
+    /// This many mines, none around the first click, placed so the rest can be cleared without guessing
+    NoGuess {
+        count: usize,
+        attempts: usize,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct ForbidAdjacentBuilder {
+    pub nouns: Vec<String>,
+    pub neighborhood: Neighborhood,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum ActionBuilder {
+    TransformTo(String),
+    CycleThrough(Vec<String>),
+    ToggleBetween(String, String),
+    Reveal,
+    FloodReveal(Neighborhood),
+    Chord {
+        count: usize,
+        flags: Vec<String>,
+        #[serde(default)]
+        neighborhood: Neighborhood,
+    },
+    Sequence(Vec<ActionBuilder>),
+}
+impl ActionBuilder {
+    pub fn to_action(self, noun_map: &HashMap<String, usize>) -> Action {
+        let to_ids = |names: Vec<String>| names.iter().map(|name| noun_map[name]).collect();
+        match self {
+            Self::TransformTo(next) => Action::TransformTo(noun_map[&next]),
+            Self::CycleThrough(cycle) => Action::CycleThrough(to_ids(cycle)),
+            Self::ToggleBetween(a, b) => Action::ToggleBetween(noun_map[&a], noun_map[&b]),
+            Self::Reveal => Action::Reveal,
+            Self::FloodReveal(neighborhood) => Action::FloodReveal(neighborhood),
+            Self::Chord { count, flags, neighborhood } => Action::Chord {
+                count,
+                flags: to_ids(flags),
+                neighborhood,
+            },
+            Self::Sequence(actions) => Action::Sequence(
+                actions.into_iter().map(|action| action.to_action(noun_map)).collect()
+            ),
+        }
+    }
+
+    pub fn targets(&self) -> Vec<&str> {
+        match self {
+            Self::TransformTo(next) => vec![next],
+            Self::CycleThrough(names) | Self::Chord { flags: names, .. } => names.iter().map(String::as_str).collect(),
+            Self::ToggleBetween(a, b) => vec![a, b],
+            Self::Reveal | Self::FloodReveal(_) => Vec::new(),
+            Self::Sequence(actions) => actions.iter().flat_map(Self::targets).collect(),
+        }
+    }
+
+    /// The key of the first list in this action that must not be empty but is
+    pub fn empty_list(&self) -> Option<&'static str> {
+        match self {
+            Self::CycleThrough(cycle) if cycle.is_empty() => Some("CycleThrough"),
+            Self::Sequence(actions) => actions.iter().find_map(Self::empty_list),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum CountBuilder {
+    Exactly(usize),
+    AtLeast(usize),
+    AtMost(usize),
+    Between(usize, usize),
+    SameAs(Vec<String>),
+}
+impl CountBuilder {
+    pub fn to_count(self, noun_map: &HashMap<String, usize>) -> Count {
+        match self {
+            Self::Exactly(count) => Count::Exactly(count),
+            Self::AtLeast(min) => Count::AtLeast(min),
+            Self::AtMost(max) => Count::AtMost(max),
+            Self::Between(min, max) => Count::Between(min, max),
+            Self::SameAs(nouns) => Count::SameAs(nouns.iter().map(|name| noun_map[name]).collect()),
+        }
+    }
+
+    pub fn targets(&self) -> Vec<&str> {
+        match self {
+            Self::SameAs(nouns) => nouns.iter().map(String::as_str).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// How the board splits into regions
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub enum RegionsBuilder {
+    #[default]
+    None,
+    /// Rectangles of `width` x `height` cells tiling the board, e.g. `Blocks(3, 3)` for Sudoku boxes
+    Blocks(usize, usize),
+    /// A string of one character per cell for each row; cells sharing a character share a region, and `.` is in none
+    Map(Vec<String>),
+}
+impl RegionsBuilder {
+    pub fn check(&self, width: usize, height: usize) -> Result<(), BuildError> {
+        match self {
+            Self::None => Ok(()),
+            &Self::Blocks(block_width, block_height) => {
+                if block_width == 0 || block_height == 0 || !width.is_multiple_of(block_width) || !height.is_multiple_of(block_height) {
+                    return Err(BuildError::UntiledBlocks { block_width, block_height, width, height });
+                }
+                Ok(())
+            },
+            Self::Map(rows) => {
+                let map_width = rows.iter().map(|row| row.chars().count()).find(|&len| len != width).unwrap_or(width);
+                if map_width != width || rows.len() != height {
+                    return Err(BuildError::RegionMapSize { map_width, map_height: rows.len(), width, height });
+                }
+                Ok(())
+            },
+        }
+    }
+
+    /// Blocks in reading order, and map regions in the order their characters first appear
+    pub fn to_regions(&self, width: usize, height: usize) -> Vec<Vec<(usize, usize)>> {
+        match self {
+            Self::None => Vec::new(),
+            &Self::Blocks(block_width, block_height) => (0..height).step_by(block_height)
+                .flat_map(|top| (0..width).step_by(block_width).map(move |left| (left, top)))
+                .map(|(left, top)| (top..top + block_height)
+                    .flat_map(|y| (left..left + block_width).map(move |x| (x, y)))
+                    .collect())
+                .collect(),
+            Self::Map(rows) => {
+                let mut names = Vec::new();
+                let mut regions: Vec<Vec<(usize, usize)>> = Vec::new();
+                for (y, row) in rows.iter().enumerate() {
+                    for (x, name) in row.chars().enumerate() {
+                        if name == '.' { continue }
+                        let index = names.iter().position(|&known| known == name).unwrap_or_else(|| {
+                            names.push(name);
+                            regions.push(Vec::new());
+                            names.len() - 1
+                        });
+                        regions[index].push((x, y));
+                    }
+                }
+                regions
+            },
+        }
+    }
+}
+
+/// The groups a rule is checked in, as written in a catalogue
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum ScopeBuilder {
+    Board,
+    Rows,
+    Columns,
+    Region(Vec<(usize, usize)>),
+    /// Each of the game's `regions`
+    Regions,
+}
+impl ScopeBuilder {
+    pub fn to_scope(self, regions: &[Vec<(usize, usize)>]) -> Scope {
+        match self {
+            Self::Board => Scope::Board,
+            Self::Rows => Scope::Rows,
+            Self::Columns => Scope::Columns,
+            Self::Region(cells) => Scope::Region(cells),
+            Self::Regions => Scope::Regions(regions.to_vec()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum RuleBuilder {
+    Count {
+        scope: ScopeBuilder,
+        nouns: Vec<String>,
+        count: CountBuilder,
+    },
+    Adjacent {
+        of: Vec<String>,
+        nouns: Vec<String>,
+        count: CountBuilder,
+        #[serde(default)]
+        neighborhood: Neighborhood,
+    },
+    Unique {
+        scope: ScopeBuilder,
+        #[serde(default)]
+        nouns: Vec<String>,
+    },
+    Level {
+        scope: ScopeBuilder,
+        nouns: Vec<String>,
+    },
+    Paired {
+        of: Vec<String>,
+        with: Vec<String>,
+        #[serde(default)]
+        neighborhood: Neighborhood,
+    },
+    And(Vec<RuleBuilder>),
+    Or(Vec<RuleBuilder>),
+    Not(Box<RuleBuilder>),
+}
+impl RuleBuilder {
+    pub fn to_rule(self, noun_map: &HashMap<String, usize>, regions: &[Vec<(usize, usize)>]) -> Rule {
+        let to_ids = |names: Vec<String>| names.iter().map(|name| noun_map[name]).collect();
+        let to_rules = |rules: Vec<RuleBuilder>| rules.into_iter().map(|rule| rule.to_rule(noun_map, regions)).collect();
+        match self {
+            Self::Count { scope, nouns, count } => Rule::Count {
+                scope: scope.to_scope(regions),
+                nouns: to_ids(nouns),
+                count: count.to_count(noun_map),
+            },
+            Self::Adjacent { of, nouns, count, neighborhood } => Rule::Adjacent {
+                of: to_ids(of),
+                nouns: to_ids(nouns),
+                count: count.to_count(noun_map),
+                neighborhood,
+            },
+            Self::Unique { scope, nouns } => Rule::Unique {
+                scope: scope.to_scope(regions),
+                nouns: to_ids(nouns),
+            },
+            Self::Level { scope, nouns } => Rule::Level {
+                scope: scope.to_scope(regions),
+                nouns: to_ids(nouns),
+            },
+            Self::Paired { of, with, neighborhood } => Rule::Paired {
+                of: to_ids(of),
+                with: to_ids(with),
+                neighborhood,
+            },
+            Self::And(rules) => Rule::And(to_rules(rules)),
+            Self::Or(rules) => Rule::Or(to_rules(rules)),
+            Self::Not(rule) => Rule::Not(Box::new(rule.to_rule(noun_map, regions))),
+        }
+    }
+
+    pub fn targets(&self) -> Vec<&str> {
+        fn names(nouns: &[String]) -> Vec<&str> {
+            nouns.iter().map(String::as_str).collect()
+        }
+        match self {
+            Self::Count { nouns, count, .. } => [names(nouns), count.targets()].concat(),
+            Self::Adjacent { of, nouns, count, .. } => [names(of), names(nouns), count.targets()].concat(),
+            Self::Unique { nouns, .. } | Self::Level { nouns, .. } => names(nouns),
+            Self::Paired { of, with, .. } => [names(of), names(with)].concat(),
+            Self::And(rules) | Self::Or(rules) => rules.iter().flat_map(Self::targets).collect(),
+            Self::Not(rule) => rule.targets(),
+        }
+    }
+
+    pub fn region_cells(&self) -> Vec<(usize, usize)> {
+        match self {
+            Self::Count { scope: ScopeBuilder::Region(cells), .. }
+            | Self::Unique { scope: ScopeBuilder::Region(cells), .. }
+            | Self::Level { scope: ScopeBuilder::Region(cells), .. } => cells.clone(),
+            Self::Count { .. } | Self::Adjacent { .. } | Self::Unique { .. } | Self::Level { .. } | Self::Paired { .. } => Vec::new(),
+            Self::And(rules) | Self::Or(rules) => rules.iter().flat_map(Self::region_cells).collect(),
+            Self::Not(rule) => rule.region_cells(),
+        }
+    }
+
+    /// Whether any part of the rule is checked in the game's `Regions`
+    pub fn uses_regions(&self) -> bool {
+        match self {
+            Self::Count { scope, .. } | Self::Unique { scope, .. } | Self::Level { scope, .. } => matches!(scope, ScopeBuilder::Regions),
+            Self::Adjacent { .. } | Self::Paired { .. } => false,
+            Self::And(rules) | Self::Or(rules) => rules.iter().any(Self::uses_regions),
+            Self::Not(rule) => rule.uses_regions(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TrackBuilder {
+    pub side: Side,
+    pub nouns: Vec<String>,
+    pub numbers: NumbersBuilder,
+}
+impl TrackBuilder {
+    pub fn check(&self, width: usize, height: usize) -> Result<(), BuildError> {
+        let lines = self.side.lines(width, height);
+        match &self.numbers {
+            NumbersBuilder::Given(numbers) if numbers.len() != lines => {
+                Err(BuildError::TrackLength { side: self.side, found: numbers.len(), lines })
+            },
+            NumbersBuilder::Given(_) | NumbersBuilder::Solution(_) => Ok(()),
+        }
+    }
+
+    pub fn to_track(self, noun_map: &HashMap<String, usize>) -> Track {
+        let to_ids = |names: Vec<String>| names.iter().map(|name| noun_map[name]).collect();
+        let Self { side, nouns, numbers } = self;
+        Track {
+            side,
+            nouns: to_ids(nouns),
+            numbers: match numbers {
+                NumbersBuilder::Given(numbers) => Numbers::Given(numbers),
+                NumbersBuilder::Solution(nouns) => Numbers::Solution(to_ids(nouns)),
+            },
+            clues: Vec::new(),
+        }
+    }
+
+    pub fn targets(&self) -> Vec<&str> {
+        let mut targets = self.nouns.iter().map(String::as_str).collect::<Vec<_>>();
+        if let NumbersBuilder::Solution(nouns) = &self.numbers {
+            targets.extend(nouns.iter().map(String::as_str));
+        }
+        targets
+    }
+}
+
+/// A track's clues, written out with `None` for lines without one, or counted on each generated board
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum NumbersBuilder {
+    Given(Vec<Option<usize>>),
+    Solution(Vec<String>),
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct RevealBuilder {
+    pub count: Vec<String>,
+    pub into: Vec<String>,
+    pub neighborhood: Neighborhood,
+}
+impl RevealBuilder {
+    pub fn to_reveal(self, noun_map: &HashMap<String, usize>) -> Reveal {
+        let Self { count, into, neighborhood } = self;
+        Reveal {
+            count: count.iter().map(|name| noun_map[name]).collect(),
+            into: into.iter().map(|name| noun_map[name]).collect(),
+            neighborhood,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CATALOGUE: &str = include_str!("../../assets/games/default.games.ron");
+
+    #[test]
+    fn reads_the_default_catalogue_without_looks() {
+        let games = parse_games(CATALOGUE).unwrap().into_iter()
+            .map(|game_builder| game_builder.to_game().unwrap())
+            .collect::<Vec<_>>();
+        let latin_square = games.iter().find(|game| game.name == "Latin Square").unwrap();
+        assert!(latin_square.nouns.noun_map.contains_key("Blank"));
+        assert!(!latin_square.rules.is_empty());
+        let mini_sudoku = games.iter().find(|game| game.name == "Mini Sudoku").unwrap();
+        assert_eq!(mini_sudoku.regions.len(), 4);
+    }
+
+    #[test]
+    fn unknown_cells_are_errors() {
+        let source = r#"[(name: "Broken", noun_builder: (spatial_element_builder: (cells: [
+            (name: "Empty", gen_method: Default, l_click: TransformTo("Full")),
+        ])))]"#;
+        let game_builder = parse_games(source).unwrap().remove(0);
+        assert!(matches!(game_builder.to_game(), Err(BuildError::UnknownCell { target, .. }) if target == "Full"));
+    }
+
+    #[test]
+    fn every_mistake_in_a_game_is_reported() {
+        let source = r#"[(name: "Bad", width: 2, height: 2, noun_builder: (spatial_element_builder: (cells: [
+            (name: "Empty", gen_method: Random(0.5)),
+            (name: "Empty"),
+            (name: "Mine", gen_method: CountRange(5, 3)),
+            (name: "Flag", gen_method: Count(5)),
+        ])))]"#;
+        let game_builder = parse_games(source).unwrap().remove(0);
+        let errors = game_builder.validate().into_iter().map(|invalid| invalid.error).collect::<Vec<_>>();
+        assert!(matches!(&errors[..], [
+            BuildError::DuplicateCell(_),
+            BuildError::EmptyCountRange { min: 5, max: 3, .. },
+            BuildError::CountExceedsBoard { count: 5, cells: 4, .. },
+            BuildError::MissingDefault(_),
+        ]), "{errors:?}");
+        assert!(matches!(game_builder.to_game(), Err(BuildError::DuplicateCell(name)) if name == "Empty"));
+
+        let crowded = r#"[(name: "Crowded", width: 3, height: 3, noun_builder: (spatial_element_builder: (cells: [
+            (name: "Empty", gen_method: Default),
+            (name: "Mine", gen_method: NoGuess(count: 1, attempts: 1)),
+        ])))]"#;
+        let mut game_builder = parse_games(crowded).unwrap().remove(0);
+        assert!(matches!(game_builder.clone().to_game(), Err(BuildError::NoGuessExceedsBoard { count: 1, room: 0, .. })));
+        game_builder.width = 4;
+        assert!(game_builder.to_game().is_ok());
+
+        let generated = r#"[(name: "Generated", width: 2, height: 2, puzz_link: (puzzle: Aquarium, generate: true, cells: []),
+            noun_builder: (spatial_element_builder: (cells: [(name: "Empty", gen_method: Default)])))]"#;
+        let mut game_builder = parse_games(generated).unwrap().remove(0);
+        assert!(matches!(game_builder.clone().to_game(), Err(BuildError::NoLinkGenerator(PuzzleType::Aquarium))));
+        game_builder.puzz_link.as_mut().unwrap().puzzle = PuzzleType::Sudoku;
+        assert!(matches!(game_builder.to_game(), Err(BuildError::NoLinkDigit(1))));
+    }
+
+    #[test]
+    fn splits_the_board_into_blocks_or_mapped_regions() {
+        assert_eq!(RegionsBuilder::Blocks(2, 1).to_regions(4, 2), vec![
+            vec![(0, 0), (1, 0)], vec![(2, 0), (3, 0)], vec![(0, 1), (1, 1)], vec![(2, 1), (3, 1)],
+        ]);
+        let map = RegionsBuilder::Map(vec!["bba".to_string(), "b.a".to_string()]);
+        assert_eq!(map.to_regions(3, 2), vec![vec![(0, 0), (1, 0), (0, 1)], vec![(2, 0), (2, 1)]]);
+        assert!(matches!(map.check(3, 3), Err(BuildError::RegionMapSize { map_height: 2, .. })));
+        assert!(matches!(RegionsBuilder::Blocks(3, 3).check(4, 4), Err(BuildError::UntiledBlocks { .. })));
+    }
+
+    #[test]
+    fn rules_check_each_region() {
+        let source = r#"[(name: "Boxes", width: 2, height: 2, regions: Map(["ab", "ab"]),
+            noun_builder: (spatial_element_builder: (cells: [(name: "Empty", gen_method: Default), (name: "Star")])),
+            rules: [Count(scope: Regions, nouns: ["Star"], count: Exactly(1))],
+        )]"#;
+        let game = parse_games(source).unwrap().remove(0).to_game().unwrap();
+        assert!(game.is_solved(&vec![vec![1, 0], vec![0, 1]]));
+        assert!(!game.is_solved(&vec![vec![1, 0], vec![1, 0]]));
+        assert_eq!(game.region_map(), vec![vec![Some(0), Some(1)], vec![Some(0), Some(1)]]);
+
+        let mut game_builder = parse_games(source).unwrap().remove(0);
+        game_builder.regions = RegionsBuilder::None;
+        assert!(matches!(game_builder.to_game(), Err(BuildError::NoRegions)));
+    }
+
+    #[test]
+    fn tracks_count_the_solution_or_take_given_clues() {
+        let source = r#"[(name: "Tracked", width: 3, height: 2,
+            noun_builder: (spatial_element_builder: (cells: [(name: "Empty", gen_method: Default), (name: "Hidden"), (name: "Tent")])),
+            rules: [Count(scope: Board, nouns: ["Hidden"], count: Exactly(0))],
+            tracks: [
+                (side: Top, nouns: ["Tent"], numbers: Solution(["Hidden"])),
+                (side: Left, nouns: ["Tent"], numbers: Given([1, None])),
+            ],
+        )]"#;
+        let mut game = parse_games(source).unwrap().remove(0).to_game().unwrap();
+        game.fill_tracks(&vec![vec![0, 1, 0], vec![0, 0, 0]]);
+        assert_eq!(game.tracks[0].clues, vec![Some(0), Some(1), Some(0)]);
+        assert_eq!(game.tracks[1].clues, vec![Some(1), None]);
+        assert!(game.is_solved(&vec![vec![0, 2, 0], vec![0, 0, 0]]));
+        assert!(!game.is_solved(&vec![vec![2, 0, 0], vec![0, 0, 0]]));
+
+        let mut game_builder = parse_games(source).unwrap().remove(0);
+        game_builder.height = 3;
+        assert!(matches!(game_builder.to_game(), Err(BuildError::TrackLength { side: Side::Left, found: 2, lines: 3 })));
+    }
+}
@@ -0,0 +1,285 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use crate::rules::*;
+use crate::link::PuzzLink;
+
+pub type NounID = usize;
+
+#[derive(Clone, Debug)]
+pub enum Noun {
+    SpatialElement(SpatialElement),
+}
+impl Noun {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::SpatialElement(SpatialElement::Cell { name, .. }) => name,
+        }
+    }
+
+    pub fn blank(&self) -> bool {
+        match self {
+            Self::SpatialElement(SpatialElement::Cell { blank, .. }) => *blank,
+        }
+    }
+
+    pub fn l_click(&self) -> Option<&Action> {
+        match self {
+            Self::SpatialElement(SpatialElement::Cell { l_click, .. }) => l_click.as_ref(),
+        }
+    }
+
+    pub fn r_click(&self) -> Option<&Action> {
+        match self {
+            Self::SpatialElement(SpatialElement::Cell { r_click, .. }) => r_click.as_ref(),
+        }
+    }
+
+    pub fn reveal(&self) -> Option<&Reveal> {
+        match self {
+            Self::SpatialElement(SpatialElement::Cell { reveal, .. }) => reveal.as_ref(),
+        }
+    }
+
+    pub fn counts_as(&self) -> Option<NounID> {
+        match self {
+            Self::SpatialElement(SpatialElement::Cell { counts_as, .. }) => *counts_as,
+        }
+    }
+}
+
+/// What a noun does, without how it looks, which the game crate keeps alongside by `NounID`
+#[derive(Clone, Debug)]
+pub enum SpatialElement {
+    Cell {
+        name: String,
+        l_click: Option<Action>,
+        r_click: Option<Action>,
+        reveal: Option<Reveal>,
+        /// Still to be decided by the player, which hints fill in
+        blank: bool,
+        /// The noun the rules take it for, e.g. a sudoku's given digit for the one players write
+        counts_as: Option<NounID>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Neighborhood {
+    Orthogonal,
+    Diagonal,
+    #[default]
+    Moore,
+}
+impl Neighborhood {
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            Self::Orthogonal => &[(0, -1), (-1, 0), (1, 0), (0, 1)],
+            Self::Diagonal => &[(-1, -1), (1, -1), (-1, 1), (1, 1)],
+            Self::Moore => &[(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)],
+        }
+    }
+
+    pub fn neighbors(&self, x: usize, y: usize, width: usize, height: usize) -> Vec<(usize, usize)> {
+        self.offsets().iter()
+            .filter_map(|&(dx, dy)| {
+                let nx = x.checked_add_signed(dx).filter(|&nx| nx < width)?;
+                let ny = y.checked_add_signed(dy).filter(|&ny| ny < height)?;
+                Some((nx, ny))
+            })
+            .collect()
+    }
+}
+
+/// A game as far as playing it goes: its size, nouns and rules
+#[derive(Clone, Debug, Default)]
+pub struct Game {
+    pub name: String,
+    pub width: usize,
+    pub height: usize,
+    pub nouns: Nouns,
+    pub rules: Vec<Rule>,
+    /// The cells of each region, e.g. Sudoku boxes or Aquarium tanks; cells may be in none
+    pub regions: Vec<Vec<(usize, usize)>>,
+    /// Numbers outside the board, e.g. the tents in each row
+    pub tracks: Vec<Track>,
+    /// What the tracks' clues ask of their rows and columns, see `fill_tracks`
+    pub clue_rules: Vec<Rule>,
+    /// How the board reads and writes puzz.link URLs, for games that have a puzz.link counterpart
+    pub puzz_link: Option<PuzzLink>,
+}
+impl Game {
+    /// Replaces the regions, e.g. once a linked puzzle resized the board, along with those the rules are checked in
+    pub fn set_regions(&mut self, regions: Vec<Vec<(usize, usize)>>) {
+        for rule in self.rules.iter_mut() {
+            rule.set_regions(&regions);
+        }
+        self.regions = regions;
+    }
+
+    /// Which region each cell is in, `region_map[y][x]`
+    pub fn region_map(&self) -> Vec<Vec<Option<usize>>> {
+        let mut region_map = vec![vec![None; self.width]; self.height];
+        for (index, region) in self.regions.iter().enumerate() {
+            for &(x, y) in region.iter() {
+                region_map[y][x] = Some(index);
+            }
+        }
+        region_map
+    }
+
+    /// A game without rules can't be won, rather than being won from the start
+    pub fn is_solved(&self, board: &impl Board) -> bool {
+        let board = &self.counted(board);
+        let mut rules = self.rules.iter().chain(self.clue_rules.iter()).peekable();
+        rules.peek().is_some() && rules.all(|rule| rule.holds(board))
+    }
+
+    /// The cells `Paired` rules leave without a partner on the board as it stands: cells of `of` straight away, and cells
+    /// of `with` once there are as many of `of`, e.g. tents without a tree of their own, then trees no tent was left for
+    pub fn unpaired(&self, board: &impl Board) -> Vec<(usize, usize)> {
+        let board = &self.counted(board);
+        self.rules.iter().flat_map(|rule| rule.unpaired(board)).collect()
+    }
+
+    /// The board as the rules see it, each noun taken for the one it counts as
+    pub(crate) fn counted<'a, B: Board>(&'a self, board: &'a B) -> Counted<'a, B> {
+        Counted { board, nouns: &self.nouns.nouns }
+    }
+
+    /// Works out every track's clues for the board play starts from, which `Solution` numbers are counted on
+    pub fn fill_tracks(&mut self, start: &impl Board) {
+        self.clue_rules.clear();
+        for track in self.tracks.iter_mut() {
+            track.clues = match &track.numbers {
+                Numbers::Given(numbers) => numbers.clone(),
+                Numbers::Solution(nouns) => (0..track.side.lines(self.width, self.height))
+                    .map(|index| Some(start.count_in(&track.side.line(index, self.width, self.height), nouns)))
+                    .collect(),
+            };
+            for (index, &clue) in track.clues.iter().enumerate() {
+                let Some(count) = clue else { continue };
+                self.clue_rules.push(Rule::Count {
+                    scope: track.side.scope(index),
+                    nouns: track.nouns.clone(),
+                    count: Count::Exactly(count),
+                });
+            }
+        }
+    }
+
+}
+
+pub(crate) struct Counted<'a, B: Board> {
+    board: &'a B,
+    nouns: &'a [Noun],
+}
+impl<B: Board> Board for Counted<'_, B> {
+    fn width(&self) -> usize {
+        self.board.width()
+    }
+
+    fn height(&self) -> usize {
+        self.board.height()
+    }
+
+    fn noun(&self, x: usize, y: usize) -> NounID {
+        let noun_id = self.board.noun(x, y);
+        self.nouns.get(noun_id).and_then(Noun::counts_as).unwrap_or(noun_id)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Nouns {
+    pub nouns: Vec<Noun>,
+    pub noun_map: HashMap<String, NounID>,
+    pub gen_config: GenConfig,
+}
+#[derive(Clone, Default, Debug)]
+pub struct GenConfig {
+    pub default: NounID,
+    pub probs: Vec<(f64, NounID)>,
+    /// Nouns placed on between `min` and `max` cells, as `(noun, min, max)`
+    pub counts: Vec<(NounID, usize, usize)>,
+    /// Nouns placed next to each cell of another noun, as `(noun, on, neighborhood)`
+    pub depends: Vec<(NounID, NounID, Neighborhood)>,
+    /// Nouns that may never be generated next to any of the listed nouns
+    pub forbids: HashMap<NounID, (Vec<NounID>, Neighborhood)>,
+    /// Mines laid out so the board can be cleared from the first click without guessing
+    pub no_guess: Option<NoGuess>,
+}
+
+/// `count` cells of `noun`, redrawn up to `attempts` times until deduction alone clears the board
+#[derive(Clone, Copy, Debug)]
+pub struct NoGuess {
+    pub noun: NounID,
+    pub count: usize,
+    pub attempts: usize,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    TransformTo(NounID),
+    CycleThrough(Vec<NounID>),
+    ToggleBetween(NounID, NounID),
+    Reveal,
+    FloodReveal(Neighborhood),
+    Chord {
+        count: usize,
+        flags: Vec<NounID>,
+        neighborhood: Neighborhood,
+    },
+    Sequence(Vec<Action>),
+}
+
+/// What a cell turns into when revealed: `into[n]` where `n` counts `count` nouns in its neighborhood
+#[derive(Clone, Debug)]
+pub struct Reveal {
+    pub count: Vec<NounID>,
+    pub into: Vec<NounID>,
+    pub neighborhood: Neighborhood,
+}
+
+/// A row of clues along one side of the board, one per column above or below it and one per row beside it
+#[derive(Clone, Debug)]
+pub struct Track {
+    pub side: Side,
+    /// What each clue counts in its row or column
+    pub nouns: Vec<NounID>,
+    pub numbers: Numbers,
+    /// The numbers as shown, `None` for a line without a clue, once `Game::fill_tracks` worked them out
+    pub clues: Vec<Option<usize>>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Numbers {
+    Given(Vec<Option<usize>>),
+    /// How many of these nouns each line has on the generated board, e.g. tents it hides
+    Solution(Vec<NounID>),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+impl Side {
+    /// Whether the clues count columns rather than rows
+    pub fn counts_columns(&self) -> bool {
+        matches!(self, Self::Top | Self::Bottom)
+    }
+
+    /// How many clues fit along this side
+    pub fn lines(&self, width: usize, height: usize) -> usize {
+        if self.counts_columns() { width } else { height }
+    }
+
+    pub fn scope(&self, index: usize) -> Scope {
+        if self.counts_columns() { Scope::Column(index) } else { Scope::Row(index) }
+    }
+
+    /// The cells of the `index`th row or column
+    pub fn line(&self, index: usize, width: usize, height: usize) -> Vec<(usize, usize)> {
+        self.scope(index).groups(width, height).remove(0)
+    }
+}
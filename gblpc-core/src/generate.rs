@@ -0,0 +1,318 @@
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use thiserror::Error;
+use crate::game::*;
+use crate::no_guess;
+use crate::play::{Click, Play};
+
+/// How many times a board is drawn again when forbidden neighbours leave no room for every noun asked for
+const ATTEMPTS: usize = 100;
+
+/// A board that still came up short of some noun after every attempt
+#[derive(Clone, Debug, Error)]
+#[error("placed {placed} of the {wanted} `{noun}` cells asked for in {ATTEMPTS} attempts")]
+pub struct Shortfall {
+    pub noun: String,
+    pub wanted: usize,
+    pub placed: usize,
+    /// The last board drawn, short as it is
+    pub board: Vec<Vec<NounID>>,
+}
+
+impl Game {
+    /// Draws a noun for every cell from the seed, with any no-guess mines kept clear of the middle of the board
+    pub fn generate(&self, seed: u64) -> Result<Vec<Vec<NounID>>, Shortfall> {
+        self.generate_around(seed, (self.width / 2, self.height / 2))
+    }
+
+    /// Draws a noun for every cell from the seed: no-guess mines first, then counted nouns, then random ones, then nouns
+    /// placed next to others, then the default. The no-guess mines leave `start` and its neighbours clear. A draw that
+    /// can't place every counted noun, or one beside every cell it depends on, is thrown away and drawn again. Sudokus
+    /// whose `puzz_link` asks for it take their givens from the puzzle generator instead.
+    pub fn generate_around(&self, seed: u64, start: (usize, usize)) -> Result<Vec<Vec<NounID>>, Shortfall> {
+        if let Some(givens) = self.generated_givens(seed) { return Ok(givens) }
+        // ChaCha's stream is defined the same on every platform and word size, unlike `StdRng` or `thread_rng`
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut drawn = self.draw(&mut rng, start);
+        for _ in 1..ATTEMPTS {
+            if drawn.is_ok() { break }
+            drawn = self.draw(&mut rng, start);
+        }
+        drawn
+    }
+
+    /// Draws a generated no-guess board again around the first cell a left click reveals on it, then marks the cells
+    /// right-clicked before then again. Leaves the board be for other clicks, other games and once anything is open.
+    pub fn redraw_for_first_reveal(&self, play: &mut Play, seed: u64, (x, y): (usize, usize), click: Click) -> Result<(), Shortfall> {
+        if click != Click::Left || self.nouns.gen_config.no_guess.is_none() { return Ok(()) }
+        let opened = self.nouns.nouns.iter().filter_map(Noun::reveal).flat_map(|reveal| reveal.into.iter()).collect::<Vec<_>>();
+        if play.cells.iter().flatten().any(|noun_id| opened.contains(&noun_id)) { return Ok(()) }
+        let mut redrawn = Play::new(self.generate_around(seed, (x, y))?);
+        // Nothing is open yet, so every step so far was a right click on the one cell it changed
+        for step in play.history.steps() {
+            if let Some(change) = step.changes.first() {
+                redrawn.click(self, change.x, change.y, Click::Right);
+            }
+        }
+        *play = redrawn;
+        Ok(())
+    }
+
+    fn draw(&self, rng: &mut ChaCha8Rng, start: (usize, usize)) -> Result<Vec<Vec<NounID>>, Shortfall> {
+        let mut board = Generating {
+            width: self.width,
+            height: self.height,
+            cells: vec![vec![None; self.width]; self.height],
+            gen_config: &self.nouns.gen_config,
+        };
+        let GenConfig { default, probs, counts, depends, no_guess, .. } = &self.nouns.gen_config;
+        // The first noun that fell short, as `(noun, wanted, placed)`
+        let mut short = None;
+
+        if let &Some(NoGuess { noun, count, attempts }) = no_guess {
+            match no_guess::generate(self.width, self.height, count, start, attempts, rng) {
+                Ok((mines, _)) => {
+                    for (x, y) in (0..self.height).flat_map(|y| (0..self.width).map(move |x| (x, y))) {
+                        if mines[y][x] {
+                            board.cells[y][x] = Some(noun);
+                        }
+                    }
+                },
+                Err(_) => { short.get_or_insert((noun, count, 0)); },
+            }
+        }
+
+        for &(noun_id, min, max) in counts.iter() {
+            // Sample in u32 since `usize` ranges draw differently on 32-bit targets like wasm
+            let count = rng.gen_range(min as u32..=max as u32) as usize;
+            let mut free = board.free_cells();
+            free.shuffle(rng);
+            let mut placed = 0;
+            for (x, y) in free {
+                if placed == count { break }
+                if board.try_place(x, y, noun_id) {
+                    placed += 1;
+                }
+            }
+            if placed < count {
+                short.get_or_insert((noun_id, count, placed));
+            }
+        }
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if board.cells[y][x].is_some() { continue }
+                let r: f64 = rng.gen();
+                if let Some(&(_, noun_id)) = probs.iter().find(|(prob, _)| r <= *prob) {
+                    board.try_place(x, y, noun_id);
+                }
+            }
+        }
+
+        for &(noun_id, on, neighborhood) in depends.iter() {
+            let anchors = board.cells_of(on);
+            let mut placed = 0;
+            for &(x, y) in anchors.iter() {
+                let mut free = neighborhood.neighbors(x, y, self.width, self.height);
+                free.retain(|&(nx, ny)| board.cells[ny][nx].is_none());
+                free.shuffle(rng);
+                if free.into_iter().any(|(nx, ny)| board.try_place(nx, ny, noun_id)) {
+                    placed += 1;
+                }
+            }
+            if placed < anchors.len() {
+                short.get_or_insert((noun_id, anchors.len(), placed));
+            }
+        }
+
+        let board = board.cells.into_iter()
+            .map(|row| row.into_iter().map(|cell| cell.unwrap_or(*default)).collect())
+            .collect();
+        match short {
+            Some((noun_id, wanted, placed)) => {
+                Err(Shortfall { noun: self.nouns.nouns[noun_id].name().to_string(), wanted, placed, board })
+            },
+            None => Ok(board),
+        }
+    }
+}
+
+// A board part way through generation, where `None` is still free for the default noun
+struct Generating<'a> {
+    width: usize,
+    height: usize,
+    cells: Vec<Vec<Option<NounID>>>,
+    gen_config: &'a GenConfig,
+}
+impl Generating<'_> {
+    fn free_cells(&self) -> Vec<(usize, usize)> {
+        self.positions(|cell| cell.is_none())
+    }
+
+    fn cells_of(&self, noun_id: NounID) -> Vec<(usize, usize)> {
+        self.positions(|cell| cell == Some(noun_id))
+    }
+
+    fn positions(&self, keep: impl Fn(Option<NounID>) -> bool) -> Vec<(usize, usize)> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| keep(self.cells[y][x]))
+            .collect()
+    }
+
+    // Checks the forbidden adjacencies of the new noun and of its neighbours, since either side can forbid the other
+    fn try_place(&mut self, x: usize, y: usize, noun_id: NounID) -> bool {
+        let forbids = &self.gen_config.forbids;
+        if let Some((nouns, neighborhood)) = forbids.get(&noun_id) {
+            let clash = neighborhood.neighbors(x, y, self.width, self.height).into_iter()
+                .any(|(nx, ny)| self.cells[ny][nx].is_some_and(|other| nouns.contains(&other)));
+            if clash { return false }
+        }
+        for (&other, (nouns, neighborhood)) in forbids.iter() {
+            if !nouns.contains(&noun_id) { continue }
+            let clash = neighborhood.neighbors(x, y, self.width, self.height).into_iter()
+                .any(|(nx, ny)| self.cells[ny][nx] == Some(other));
+            if clash { return false }
+        }
+        self.cells[y][x] = Some(noun_id);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY: NounID = 0;
+    const TENT: NounID = 1;
+    const TREE: NounID = 2;
+
+    fn tents_and_trees(tents: usize) -> Game {
+        tents_and_trees_on(8, tents)
+    }
+
+    fn tents_and_trees_on(size: usize, tents: usize) -> Game {
+        let mut gen_config = GenConfig {
+            default: EMPTY,
+            counts: vec![(TENT, tents, tents)],
+            depends: vec![(TREE, TENT, Neighborhood::Orthogonal)],
+            ..Default::default()
+        };
+        gen_config.forbids.insert(TENT, (vec![TENT], Neighborhood::Moore));
+        let nouns = ["Empty", "Tent", "Tree"].into_iter().map(named).collect();
+        Game {
+            width: size,
+            height: size,
+            nouns: Nouns { nouns, gen_config, ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    fn named(name: &str) -> Noun {
+        Noun::SpatialElement(SpatialElement::Cell { name: name.to_string(), l_click: None, r_click: None, reveal: None, blank: false, counts_as: None })
+    }
+
+    fn cells_of(board: &[Vec<NounID>], noun_id: NounID) -> Vec<(usize, usize)> {
+        board.iter().enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().filter(|&(_, &cell)| cell == noun_id).map(move |(x, _)| (x, y)))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_same_board() {
+        let config = tents_and_trees(8);
+        assert_eq!(config.generate(7).unwrap(), config.generate(7).unwrap());
+    }
+
+    #[test]
+    fn count_is_exact() {
+        for seed in 0..20 {
+            assert_eq!(cells_of(&tents_and_trees(8).generate(seed).unwrap(), TENT).len(), 8);
+        }
+    }
+
+    #[test]
+    fn tight_counts_are_drawn_again_until_they_fit() {
+        // Four tents only fit a 4x4 board one to each 2x2 corner, which a single shuffle rarely finds
+        let config = tents_and_trees_on(4, 4);
+        for seed in 0..20 {
+            let board = config.generate_around(seed, (0, 0)).unwrap();
+            let tents = cells_of(&board, TENT);
+            assert_eq!(tents.len(), 4);
+            for &(x, y) in tents.iter() {
+                assert!(Neighborhood::Orthogonal.neighbors(x, y, 4, 4).iter().any(|&(nx, ny)| board[ny][nx] == TREE));
+            }
+        }
+        let shortfall = tents_and_trees_on(4, 5).generate_around(0, (0, 0)).unwrap_err();
+        assert_eq!((shortfall.noun.as_str(), shortfall.wanted), ("Tent", 5));
+        assert!(shortfall.placed <= 4);
+        assert_eq!(cells_of(&shortfall.board, TENT).len(), shortfall.placed);
+    }
+
+    #[test]
+    fn forbidden_neighbours_never_touch() {
+        for seed in 0..20 {
+            let board = tents_and_trees(10).generate(seed).unwrap();
+            for (x, y) in cells_of(&board, TENT) {
+                assert!(Neighborhood::Moore.neighbors(x, y, 8, 8).iter().all(|&(nx, ny)| board[ny][nx] != TENT));
+            }
+        }
+    }
+
+    #[test]
+    fn no_guess_mines_leave_the_first_click_clear() {
+        const MINE: NounID = 1;
+        let gen_config = GenConfig { no_guess: Some(NoGuess { noun: MINE, count: 15, attempts: 1000 }), ..Default::default() };
+        let config = Game { width: 10, height: 10, nouns: Nouns { gen_config, ..Default::default() }, ..Default::default() };
+        for seed in 0..5 {
+            let board = config.generate_around(seed, (2, 7)).unwrap();
+            assert_eq!(board, config.generate_around(seed, (2, 7)).unwrap());
+            let mines = board.iter().map(|row| row.iter().map(|&cell| cell == MINE).collect()).collect::<Vec<Vec<_>>>();
+            assert_eq!(mines.iter().flatten().filter(|&&mine| mine).count(), 15);
+            assert!(no_guess::solves(&mines, (2, 7)));
+        }
+    }
+
+    #[test]
+    fn no_guess_mines_that_dont_fit_are_a_shortfall() {
+        let gen_config = GenConfig { no_guess: Some(NoGuess { noun: 1, count: 9, attempts: 10 }), ..Default::default() };
+        let nouns = Nouns { nouns: vec![named("Empty"), named("Mine")], gen_config, ..Default::default() };
+        let config = Game { width: 3, height: 3, nouns, ..Default::default() };
+        assert!(matches!(config.generate(0), Err(Shortfall { wanted: 9, placed: 0, .. })));
+    }
+
+    #[test]
+    fn flags_are_kept_when_the_first_reveal_redraws_the_board() {
+        let catalogue = include_str!("../../assets/games/default.games.ron");
+        let game = crate::builders::parse_games(catalogue).unwrap().into_iter()
+            .find(|game_builder| game_builder.name == "Minesweeper").unwrap()
+            .to_game().unwrap();
+        let name = |play: &Play, x: usize, y: usize| game.nouns.nouns[play.cells[y][x]].name().to_string();
+        let mut play = Play::new(game.generate(3).unwrap());
+        game.redraw_for_first_reveal(&mut play, 3, (0, 0), Click::Right).unwrap();
+        play.click(&game, 0, 0, Click::Right);
+        let flagged = play.cells.clone();
+        assert!(name(&play, 0, 0).starts_with("Flagged"));
+
+        game.redraw_for_first_reveal(&mut play, 3, (6, 6), Click::Left).unwrap();
+        assert_ne!(play.cells, flagged);
+        assert!(name(&play, 0, 0).starts_with("Flagged"));
+        assert_eq!(play.history.steps().len(), 1);
+        play.click(&game, 6, 6, Click::Left);
+        assert!(name(&play, 6, 6).starts_with("Open"));
+
+        let opened = play.cells.clone();
+        game.redraw_for_first_reveal(&mut play, 3, (9, 9), Click::Left).unwrap();
+        assert_eq!(play.cells, opened);
+    }
+
+    #[test]
+    fn dependents_sit_next_to_their_anchor() {
+        for seed in 0..20 {
+            let board = tents_and_trees(8).generate(seed).unwrap();
+            for (x, y) in cells_of(&board, TREE) {
+                assert!(Neighborhood::Orthogonal.neighbors(x, y, 8, 8).iter().any(|&(nx, ny)| board[ny][nx] == TENT));
+            }
+        }
+    }
+}
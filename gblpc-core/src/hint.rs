@@ -0,0 +1,257 @@
+use crate::game::*;
+use crate::rules::*;
+
+/// The next move that follows from the rules, or the mistake standing in the way of one
+#[derive(Clone, Debug)]
+pub struct Hint {
+    /// The cell and what it has to be, or `None` when the board already breaks a rule
+    pub cell: Option<((usize, usize), NounID)>,
+    /// The cells the explanation rests on
+    pub because: Vec<(usize, usize)>,
+    pub explanation: String,
+    /// How many rows, columns or other places it took together to rule out the other options, 0 for a mistake
+    pub places: usize,
+}
+
+impl Game {
+    /// The first blank cell that only one of its options fits, with the rules that rule out the others
+    pub fn hint(&self, board: &impl Board) -> Option<Hint> {
+        let board = &self.counted(board);
+        let options = self.options();
+        let mut trial = Trial { board, options: &options, placed: None };
+        if let Some(breach) = self.breach(&trial) {
+            return Some(Hint {
+                cell: None,
+                explanation: format!("Something's wrong: {}", self.describe(&breach)),
+                because: breach.cells,
+                places: 0,
+            });
+        }
+
+        for (x, y) in (0..self.height).flat_map(|y| (0..self.width).map(move |x| (x, y))) {
+            // What a reveal turns up is hidden, so reasoning about it would give the board away
+            if self.nouns.nouns[board.noun(x, y)].reveal().is_some() { continue }
+            let Some(cell_options) = trial.options(x, y).map(<[NounID]>::to_vec) else { continue };
+            let mut fits = Vec::new();
+            let mut breaches = Vec::new();
+            for option in cell_options {
+                trial.placed = Some(((x, y), option));
+                match self.breach(&trial) {
+                    Some(breach) => breaches.push(breach),
+                    None => fits.push(option),
+                }
+            }
+            trial.placed = None;
+            let (&[noun_id], false) = (&fits[..], breaches.is_empty()) else { continue };
+
+            let mut because = Vec::new();
+            let mut reasons = Vec::new();
+            let mut places = Vec::new();
+            for breach in breaches.iter() {
+                if !places.contains(&breach.place) {
+                    places.push(breach.place);
+                }
+                for &cell in breach.cells.iter() {
+                    if cell != (x, y) && !because.contains(&cell) {
+                        because.push(cell);
+                    }
+                }
+                let reason = self.describe(breach);
+                if !reasons.contains(&reason) {
+                    reasons.push(reason);
+                }
+            }
+            return Some(Hint {
+                cell: Some(((x, y), noun_id)),
+                because,
+                explanation: format!("({}, {}) must be {}: {}", x + 1, y + 1, self.name(noun_id), reasons.join("; ")),
+                places: places.len(),
+            });
+        }
+        None
+    }
+
+    /// The first rule the board can no longer satisfy, whatever the blank cells turn out to be
+    pub fn mistake(&self, board: &impl Board) -> Option<Breach> {
+        let board = &self.counted(board);
+        let options = self.options();
+        self.breach(&Trial { board, options: &options, placed: None })
+    }
+
+    // What each blank noun's actions and reveal can turn it into, leaving out other blank nouns
+    fn options(&self) -> Vec<Option<Vec<NounID>>> {
+        fn targets(action: &Action, into: &mut Vec<NounID>) {
+            match action {
+                &Action::TransformTo(noun_id) => into.push(noun_id),
+                Action::CycleThrough(cycle) => into.extend(cycle.iter().copied()),
+                &Action::ToggleBetween(a, b) => into.extend([a, b]),
+                Action::Sequence(actions) => actions.iter().for_each(|action| targets(action, into)),
+                Action::Reveal | Action::FloodReveal(_) | Action::Chord { .. } => (),
+            }
+        }
+        let nouns = &self.nouns.nouns;
+        nouns.iter()
+            .map(|noun| {
+                if !noun.blank() { return None }
+                let mut into = Vec::new();
+                for action in noun.l_click().into_iter().chain(noun.r_click()) {
+                    targets(action, &mut into);
+                }
+                if let Some(reveal) = noun.reveal() {
+                    into.extend(reveal.into.iter().copied());
+                }
+                let mut options = Vec::new();
+                for noun_id in into {
+                    if !nouns[noun_id].blank() && !options.contains(&noun_id) {
+                        options.push(noun_id);
+                    }
+                }
+                (!options.is_empty()).then_some(options)
+            })
+            .collect()
+    }
+
+    fn breach(&self, board: &impl PartialBoard) -> Option<Breach> {
+        self.rules.iter().chain(self.clue_rules.iter()).find_map(|rule| match rule.verdict(board) {
+            Verdict::Broken(breach) => Some(breach),
+            Verdict::Holds | Verdict::Open => None,
+        })
+    }
+
+    fn name(&self, noun_id: NounID) -> &str {
+        self.nouns.nouns[noun_id].name()
+    }
+
+    fn names(&self, nouns: &[NounID]) -> String {
+        nouns.iter().map(|&noun_id| self.name(noun_id)).collect::<Vec<_>>().join(" or ")
+    }
+
+    // e.g. "row 3 already has its 2 Tent"
+    fn describe(&self, Breach { place, reason, .. }: &Breach) -> String {
+        let place_name = match *place {
+            Place::Board => "the board".to_string(),
+            Place::Row(y) => format!("row {}", y + 1),
+            Place::Column(x) => format!("column {}", x + 1),
+            Place::Region => "the region".to_string(),
+            Place::NumberedRegion(index) => format!("region {}", index + 1),
+            Place::Around(x, y) => format!("the cell at ({}, {})", x + 1, y + 1),
+        };
+        let next_to = if matches!(place, Place::Around(..)) { " next to it" } else { "" };
+        let amount = |count: &Count, too_many: bool| match count {
+            &Count::Exactly(count) | &Count::AtMost(count) | &Count::AtLeast(count) => count.to_string(),
+            &Count::Between(low, high) => if too_many { high } else { low }.to_string(),
+            Count::SameAs(others) => format!("as many as {}", self.names(others)),
+        };
+        match reason {
+            Reason::TooMany(nouns, Count::Exactly(0) | Count::AtMost(0)) => format!("{place_name} can't have any {}{next_to}", self.names(nouns)),
+            Reason::TooMany(nouns, count) => format!("{place_name} already has its {} {}{next_to}", amount(count, true), self.names(nouns)),
+            Reason::TooFew(nouns, count) => format!("{place_name} needs {} {}{next_to}", amount(count, false), self.names(nouns)),
+            &Reason::Repeated(noun_id) => format!("{place_name} already has a {}", self.name(noun_id)),
+            Reason::Unlevel(nouns) => format!("{} in {place_name} has to fill it level from the bottom", self.names(nouns)),
+            Reason::Unpaired(nouns, with) => format!("{place_name} has a {} without a {} of its own", self.names(nouns), self.names(with)),
+            Reason::Negated => "a rule forbids it".to_string(),
+        }
+    }
+}
+
+// The board as the player sees it, with at most one blank cell tried out as one of its options
+struct Trial<'a, B: Board> {
+    board: &'a B,
+    options: &'a [Option<Vec<NounID>>],
+    placed: Option<((usize, usize), NounID)>,
+}
+impl<B: Board> Board for Trial<'_, B> {
+    fn width(&self) -> usize {
+        self.board.width()
+    }
+
+    fn height(&self) -> usize {
+        self.board.height()
+    }
+
+    fn noun(&self, x: usize, y: usize) -> NounID {
+        match self.placed {
+            Some((cell, noun_id)) if cell == (x, y) => noun_id,
+            _ => self.board.noun(x, y),
+        }
+    }
+}
+impl<B: Board> PartialBoard for Trial<'_, B> {
+    fn options(&self, x: usize, y: usize) -> Option<&[NounID]> {
+        self.options[self.noun(x, y)].as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(name: &str, blank: bool, l_click: Action) -> Noun {
+        Noun::SpatialElement(SpatialElement::Cell {
+            name: name.to_string(),
+            l_click: Some(l_click),
+            r_click: None,
+            reveal: None,
+            blank,
+            counts_as: None,
+        })
+    }
+
+    // A 3x3 Latin square: `Blank` can become any digit
+    fn latin_square() -> Game {
+        let mut config = Game { width: 3, height: 3, ..Default::default() };
+        let digits = vec![1, 2, 3];
+        config.nouns.nouns.push(cell("Blank", true, Action::CycleThrough(digits.clone())));
+        for name in ["One", "Two", "Three"] {
+            config.nouns.nouns.push(cell(name, false, Action::CycleThrough(digits.clone())));
+        }
+        config.rules = vec![
+            Rule::Count { scope: Scope::Board, nouns: vec![0], count: Count::Exactly(0) },
+            Rule::Unique { scope: Scope::Rows, nouns: Vec::new() },
+            Rule::Unique { scope: Scope::Columns, nouns: Vec::new() },
+        ];
+        config
+    }
+
+    struct Rows(Vec<Vec<NounID>>);
+    impl Board for Rows {
+        fn width(&self) -> usize { self.0[0].len() }
+        fn height(&self) -> usize { self.0.len() }
+        fn noun(&self, x: usize, y: usize) -> NounID { self.0[y][x] }
+    }
+
+    #[test]
+    fn last_digit_in_a_row() {
+        let hint = latin_square().hint(&Rows(vec![vec![1, 2, 0], vec![0; 3], vec![0; 3]])).unwrap();
+        assert_eq!(hint.cell, Some(((2, 0), 3)));
+        assert!(hint.because.contains(&(0, 0)) && hint.because.contains(&(1, 0)));
+        assert_eq!(hint.explanation, "(3, 1) must be Three: row 1 already has a One; row 1 already has a Two");
+    }
+
+    #[test]
+    fn nothing_follows_yet() {
+        assert!(latin_square().hint(&Rows(vec![vec![0; 3]; 3])).is_none());
+    }
+
+    #[test]
+    fn revealed_outcomes_count_as_options() {
+        // Hidden (0) reveals into Open (2) and can be flagged (1), and nothing may stay hidden or flagged
+        let mut config = Game { width: 2, height: 1, ..Default::default() };
+        let mut hidden = cell("Hidden", true, Action::TransformTo(1));
+        let Noun::SpatialElement(SpatialElement::Cell { reveal, .. }) = &mut hidden;
+        *reveal = Some(Reveal { count: Vec::new(), into: vec![2], neighborhood: Neighborhood::Moore });
+        config.nouns.nouns = vec![hidden, cell("Flagged", false, Action::TransformTo(0)), cell("Open", false, Action::TransformTo(2))];
+        config.rules = vec![Rule::Count { scope: Scope::Board, nouns: vec![0, 1], count: Count::Exactly(0) }];
+        assert!(config.mistake(&Rows(vec![vec![0, 2]])).is_none());
+        assert!(config.mistake(&Rows(vec![vec![1, 2]])).is_some());
+        // Whether a hidden cell opens safely isn't for a hint to say
+        assert!(config.hint(&Rows(vec![vec![0, 2]])).is_none());
+    }
+
+    #[test]
+    fn points_out_mistakes() {
+        let hint = latin_square().hint(&Rows(vec![vec![1, 1, 0], vec![0; 3], vec![0; 3]])).unwrap();
+        assert!(hint.cell.is_none());
+        assert_eq!(hint.explanation, "Something's wrong: row 1 already has a One");
+    }
+}
@@ -0,0 +1,122 @@
+use serde::{Serialize, Deserialize};
+use crate::game::NounID;
+
+/// One cell going from one noun to another
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    pub x: usize,
+    pub y: usize,
+    pub from: NounID,
+    pub to: NounID,
+}
+
+/// Every change a single action made, so a flood or chord undoes in one go
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    pub changes: Vec<Change>,
+    /// Whether the board broke no rule right after this step
+    pub correct: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    done: Vec<Step>,
+    undone: Vec<Step>,
+    // Only ever non-empty in the middle of an action
+    #[serde(skip)]
+    open: Vec<Change>,
+}
+impl History {
+    /// Adds a change to the step being recorded
+    pub fn record(&mut self, change: Change) {
+        self.open.push(change);
+    }
+
+    /// Closes the step being recorded, dropping the redo branch unless the step changed nothing
+    pub fn commit(&mut self, correct: bool) {
+        if self.open.is_empty() { return }
+        self.done.push(Step { changes: std::mem::take(&mut self.open), correct });
+        self.undone.clear();
+    }
+
+    pub fn undo(&mut self) -> Option<&Step> {
+        let step = self.done.pop()?;
+        self.undone.push(step);
+        self.undone.last()
+    }
+
+    pub fn redo(&mut self) -> Option<&Step> {
+        let step = self.undone.pop()?;
+        self.done.push(step);
+        self.done.last()
+    }
+
+    /// The steps taken and not undone, oldest first
+    pub fn steps(&self) -> &[Step] {
+        &self.done
+    }
+
+    /// Whether no step has been taken yet, undone or not
+    pub fn is_empty(&self) -> bool {
+        self.done.is_empty() && self.undone.is_empty()
+    }
+
+    /// Looks the nouns of every change up again, e.g. once the game's nouns were numbered differently
+    pub fn renumber(&mut self, renumber: impl Fn(NounID) -> NounID) {
+        for change in self.done.iter_mut().chain(self.undone.iter_mut()).flat_map(|step| step.changes.iter_mut()) {
+            change.from = renumber(change.from);
+            change.to = renumber(change.to);
+        }
+    }
+
+    /// How many steps have been taken since the board last broke no rule, counting the start as correct
+    pub fn since_correct(&self) -> usize {
+        self.done.iter().rev().take_while(|step| !step.correct).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(history: &mut History, cells: &[(usize, NounID, NounID)], correct: bool) {
+        for &(x, from, to) in cells {
+            history.record(Change { x, y: 0, from, to });
+        }
+        history.commit(correct);
+    }
+
+    #[test]
+    fn undo_and_redo_whole_steps() {
+        let mut history = History::default();
+        step(&mut history, &[(0, 0, 1), (1, 0, 1)], true);
+        step(&mut history, &[(2, 0, 2)], true);
+        assert_eq!(history.undo().unwrap().changes.len(), 1);
+        assert_eq!(history.undo().unwrap().changes.len(), 2);
+        assert!(history.undo().is_none());
+        assert_eq!(history.redo().unwrap().changes[0], Change { x: 0, y: 0, from: 0, to: 1 });
+    }
+
+    #[test]
+    fn new_step_drops_redo() {
+        let mut history = History::default();
+        step(&mut history, &[(0, 0, 1)], true);
+        history.undo();
+        step(&mut history, &[], true);
+        assert!(history.redo().is_some());
+        history.undo();
+        step(&mut history, &[(1, 0, 1)], true);
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn steps_since_correct() {
+        let mut history = History::default();
+        step(&mut history, &[(0, 0, 1)], false);
+        assert_eq!(history.since_correct(), 1);
+        step(&mut history, &[(1, 0, 1)], true);
+        step(&mut history, &[(2, 0, 1)], false);
+        step(&mut history, &[(3, 0, 1)], false);
+        assert_eq!(history.since_correct(), 2);
+    }
+}
@@ -0,0 +1,14 @@
+//! The puzzle engine without any Bevy: nouns, rules, generation, hints and play, plus the standalone puzzle definitions
+//! and their solvers. `gblpc` adapts it into ECS, and it runs as is in the CLI, tests and web workers.
+#![allow(clippy::type_complexity)]
+
+pub mod game;
+pub mod builders;
+pub mod play;
+pub mod rules;
+pub mod generate;
+pub mod hint;
+pub mod history;
+pub mod no_guess;
+pub mod puzzle;
+pub mod link;
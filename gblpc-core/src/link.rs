@@ -0,0 +1,277 @@
+use thiserror::Error;
+use crate::game::*;
+use crate::no_guess;
+use crate::puzzle::cell::Cell;
+use crate::puzzle::definition::{self, GenerateOptions, PuzzleRule, Symmetry};
+use crate::puzzle::dsl::{self, CellContent, Puzzle, PuzzleType, PuzzleDefinition, Region};
+use crate::puzzle::grid::Grid;
+
+/// The puzz.link puzzle type a game plays, and the given each listed noun stands for
+#[derive(Clone, Debug)]
+pub struct PuzzLink {
+    pub puzzle_type: PuzzleType,
+    /// Fresh boards take their givens from the puzzle generator
+    pub generate: bool,
+    /// The first noun listed for a given is the one a link lays out
+    pub cells: Vec<(NounID, CellContent)>,
+}
+impl PuzzLink {
+    fn content(&self, noun_id: NounID) -> Option<&CellContent> {
+        self.cells.iter().find(|&&(listed, _)| listed == noun_id).map(|(_, content)| content)
+    }
+
+    fn noun(&self, content: &CellContent) -> Option<NounID> {
+        self.cells.iter().find(|(_, listed)| listed == content).map(|&(noun_id, _)| noun_id)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LinkError {
+    #[error("`{0}` has no puzz.link format")]
+    NoFormat(String),
+    #[error("no game plays {0:?} puzzles")]
+    NoGame(PuzzleType),
+    #[error("`{game}` has no cell for {content:?}")]
+    NoCell { game: String, content: CellContent },
+    #[error("no layout of mines fits the numbers")]
+    NoMines,
+}
+
+/// The game that plays a linked puzzle: one of its type and size, or else the first of its type by name
+pub fn game_for<'a>(games: impl IntoIterator<Item = &'a Game>, definition: &PuzzleDefinition) -> Result<&'a Game, LinkError> {
+    let puzzle = &definition.puzzle;
+    let mut games = games.into_iter()
+        .filter(|game| game.puzz_link.as_ref().is_some_and(|puzz_link| puzz_link.puzzle_type == puzzle.puzzle_type))
+        .collect::<Vec<_>>();
+    games.sort_by(|a, b| a.name.cmp(&b.name));
+    games.iter()
+        .find(|game| (game.width, game.height) == (puzzle.grid.width, puzzle.grid.height))
+        .or(games.first())
+        .copied()
+        .ok_or_else(|| LinkError::NoGame(puzzle.puzzle_type.clone()))
+}
+
+// A sudoku digit for the puzzle generator, 0 until decided
+#[derive(Clone, Debug)]
+struct Digit {
+    pos: (usize, usize),
+    value: u8,
+    revealed: bool,
+}
+impl Cell for Digit {
+    type CellVariant = ();
+    type CellState = u8;
+    fn new(row: usize, col: usize) -> Self {
+        Self { pos: (row, col), value: 0, revealed: false }
+    }
+    fn get_pos(&self) -> (usize, usize) { self.pos }
+    fn get_variant(&self) -> &() { &() }
+    fn get_state(&self) -> &u8 { &self.value }
+    fn set_state(&mut self, state: u8) { self.value = state }
+    fn candidate_states(&self) -> Vec<u8> { (1..=9).collect() }
+    fn is_revealed(&self) -> bool { self.revealed }
+    fn reveal(&mut self) { self.revealed = true }
+    fn hide(&mut self) { self.revealed = false }
+}
+
+// Digits 1 to `size` once each in every row, column and region
+fn sudoku(size: usize, regions: &[Vec<(usize, usize)>]) -> definition::PuzzleDefinition<Digit> {
+    // Bit sets rather than hash sets, as the solver checks every rule for every candidate it tries
+    let unique = |unit: Vec<(usize, usize)>| move |grid: &Grid<Digit>| {
+        let mut seen = 0u16;
+        unit.iter()
+            .filter_map(|&(row, col)| grid.get_cell(row, col))
+            .filter(|digit| digit.revealed)
+            .all(|digit| {
+                let bit = 1 << digit.value;
+                let fresh = seen & bit == 0;
+                seen |= bit;
+                fresh
+            })
+    };
+    let mut rules = vec![PuzzleRule {
+        description: format!("digits up to {size}"),
+        condition: Box::new(move |grid: &Grid<Digit>| grid.cells.iter().flatten().all(|digit| !digit.revealed || digit.value as usize <= size)),
+    }];
+    for i in 0..size {
+        rules.push(PuzzleRule {
+            description: format!("row {i}"),
+            condition: Box::new(unique((0..size).map(|col| (i, col)).collect())),
+        });
+        rules.push(PuzzleRule {
+            description: format!("column {i}"),
+            condition: Box::new(unique((0..size).map(|row| (row, i)).collect())),
+        });
+    }
+    for (i, region) in regions.iter().enumerate() {
+        rules.push(PuzzleRule {
+            description: format!("region {i}"),
+            condition: Box::new(unique(region.iter().map(|&(x, y)| (y, x)).collect())),
+        });
+    }
+    definition::PuzzleDefinition { rows: size, cols: size, rules }
+}
+
+impl Game {
+    /// Givens for a fresh board when `puzz_link` asks the puzzle generator for them: a sudoku dug out from the seed,
+    /// symmetric and with one solution, laid out as the nouns listed for its digits
+    pub(crate) fn generated_givens(&self, seed: u64) -> Option<Vec<Vec<NounID>>> {
+        let puzz_link = self.puzz_link.as_ref().filter(|puzz_link| puzz_link.generate)?;
+        let options = GenerateOptions { symmetry: Symmetry::Rotational, target_clues: None };
+        let grid = sudoku(self.width, &self.regions).generate_puzzle_with(seed, options)?;
+        let mut cells = vec![vec![self.nouns.gen_config.default; self.width]; self.height];
+        for digit in grid.cells.iter().flatten().filter(|digit| digit.revealed) {
+            let (y, x) = digit.pos;
+            cells[y][x] = puzz_link.noun(&CellContent::Number(digit.value))?;
+        }
+        Some(cells)
+    }
+
+    /// The puzzle the board poses, given by the nouns `puzz_link` lists, e.g. a sudoku's given digits, or a
+    /// minesweeper's numbers opened so far with the mines under the rest
+    pub fn puzzle_definition(&self, cells: &[Vec<NounID>]) -> Result<PuzzleDefinition, LinkError> {
+        let puzz_link = self.puzz_link.as_ref().ok_or_else(|| LinkError::NoFormat(self.name.clone()))?;
+        let (width, height) = (self.width, self.height);
+        let mut puzzle = match puzz_link.puzzle_type {
+            PuzzleType::Sudoku => Puzzle::sudoku(width),
+            ref puzzle_type => Puzzle::new(puzzle_type.clone(), width, height),
+        };
+        if puzzle.puzzle_type == PuzzleType::Aquarium {
+            for (id, cells) in self.regions.iter().enumerate() {
+                puzzle.add_region(Region { id, cells: cells.clone() });
+            }
+            puzzle.rules.push(dsl::Rule::WaterFlowsUpward);
+        }
+        for (x, y) in (0..height).flat_map(|y| (0..width).map(move |x| (x, y))) {
+            if let Some(content) = puzz_link.content(cells[y][x]) {
+                puzzle.set(x, y, content.clone());
+            }
+        }
+        for track in self.tracks.iter() {
+            for (index, &clue) in track.clues.iter().enumerate() {
+                let Some(count) = clue.and_then(|clue| u8::try_from(clue).ok()) else { continue };
+                let rule = match (&puzzle.puzzle_type, track.side.counts_columns()) {
+                    (PuzzleType::TentsAndTrees, true) => dsl::Rule::TentsInColumn(index, count),
+                    (PuzzleType::TentsAndTrees, false) => dsl::Rule::TentsInRow(index, count),
+                    (PuzzleType::Aquarium, true) => dsl::Rule::WaterInColumn(index, count),
+                    (PuzzleType::Aquarium, false) => dsl::Rule::WaterInRow(index, count),
+                    _ => continue,
+                };
+                puzzle.rules.push(rule);
+            }
+        }
+        Ok(PuzzleDefinition::new(puzzle))
+    }
+
+    /// This game sized and divided into regions as the linked puzzle is, and the board its givens lay out
+    pub fn linked(&self, definition: &PuzzleDefinition) -> Result<(Game, Vec<Vec<NounID>>), LinkError> {
+        let puzz_link = self.puzz_link.as_ref().ok_or_else(|| LinkError::NoFormat(self.name.clone()))?;
+        let puzzle = &definition.puzzle;
+        let (width, height) = (puzzle.grid.width, puzzle.grid.height);
+        let mut game = self.clone();
+        game.width = width;
+        game.height = height;
+        game.set_regions(puzzle.regions.iter().map(|region| region.cells.clone()).collect());
+        // The link's own row and column counts stand in for whatever the tracks would count
+        let mut row_clues = vec![None; height];
+        let mut column_clues = vec![None; width];
+        for rule in puzzle.rules.iter() {
+            let (clues, index, count) = match *rule {
+                dsl::Rule::TentsInRow(y, count) | dsl::Rule::WaterInRow(y, count) => (&mut row_clues, y, count),
+                dsl::Rule::TentsInColumn(x, count) | dsl::Rule::WaterInColumn(x, count) => (&mut column_clues, x, count),
+                _ => continue,
+            };
+            if let Some(clue) = clues.get_mut(index) {
+                *clue = Some(count as usize);
+            }
+        }
+        for track in game.tracks.iter_mut() {
+            let clues = if track.side.counts_columns() { &column_clues } else { &row_clues };
+            track.numbers = Numbers::Given(clues.clone());
+        }
+        // Links only carry a minesweeper's numbers, so any layout of mines they agree with is laid under them
+        let mines = match puzzle.puzzle_type {
+            PuzzleType::Minesweeper => {
+                let numbers = (0..height)
+                    .map(|y| (0..width)
+                        .map(|x| match definition.given(x, y) {
+                            Some(&CellContent::Number(number)) => Some(number),
+                            _ => None,
+                        })
+                        .collect())
+                    .collect::<Vec<Vec<_>>>();
+                no_guess::mines_for(&numbers).ok_or(LinkError::NoMines)?
+            },
+            _ => vec![vec![false; width]; height],
+        };
+        let mut cells = vec![vec![game.nouns.gen_config.default; width]; height];
+        for (x, y) in (0..height).flat_map(|y| (0..width).map(move |x| (x, y))) {
+            let content = if mines[y][x] { Some(&CellContent::Mine) } else { definition.given(x, y) };
+            let Some(content) = content else { continue };
+            cells[y][x] = puzz_link.noun(content)
+                .ok_or_else(|| LinkError::NoCell { game: self.name.clone(), content: content.clone() })?;
+        }
+        Ok((game, cells))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalogue() -> Vec<Game> {
+        let catalogue = include_str!("../../assets/games/default.games.ron");
+        crate::builders::parse_games(catalogue).unwrap().into_iter()
+            .map(|game_builder| game_builder.to_game().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn links_lay_out_fixed_givens_on_the_game_of_their_size() {
+        let games = catalogue();
+        let definition = PuzzleDefinition::from_url("https://puzz.link/p?sudoku/4/4/1g2h3g4n").unwrap();
+        let game = game_for(games.iter(), &definition).unwrap();
+        assert_eq!(game.name, "Mini Sudoku");
+        let (game, cells) = game.linked(&definition).unwrap();
+        let name = |x: usize, y: usize| game.nouns.nouns[cells[y][x]].name();
+        assert_eq!(name(0, 0), "Given One");
+        assert!(game.nouns.nouns[cells[0][0]].l_click().is_none());
+        assert_eq!(name(1, 0), "Blank");
+
+        let shared = game.puzzle_definition(&cells).unwrap();
+        assert_eq!(shared.to_url().unwrap(), definition.to_url().unwrap());
+    }
+
+    #[test]
+    fn givens_count_as_the_digits_players_write() {
+        let games = catalogue();
+        let game = games.iter().find(|game| game.name == "Mini Sudoku").unwrap();
+        let noun = |name: &str| game.nouns.noun_map[name];
+        let mut cells = vec![vec![noun("Blank"); 4]; 4];
+        cells[0][0] = noun("Given One");
+        cells[0][1] = noun("One");
+        assert!(game.mistake(&cells).is_some());
+        cells[0][1] = noun("Two");
+        assert!(game.mistake(&cells).is_none());
+    }
+
+    #[test]
+    fn catalogue_sudokus_deal_givens_with_one_solution() {
+        let games = catalogue();
+        let game = games.iter().find(|game| game.name == "Mini Sudoku").unwrap();
+        let cells = game.generate(7).unwrap();
+        assert_eq!(cells, game.generate(7).unwrap());
+        let given = |noun_id: &NounID| game.nouns.nouns[*noun_id].name().starts_with("Given");
+        let givens = cells.iter().flatten().filter(|noun_id| given(noun_id)).count();
+        assert!((1..16).contains(&givens), "{givens} givens");
+        assert!(game.mistake(&cells).is_none());
+    }
+
+    #[test]
+    fn games_without_a_format_say_so() {
+        let games = catalogue();
+        let latin_square = games.iter().find(|game| game.name == "Latin Square").unwrap();
+        let cells = vec![vec![0; latin_square.width]; latin_square.height];
+        assert!(matches!(latin_square.puzzle_definition(&cells), Err(LinkError::NoFormat(_))));
+    }
+}
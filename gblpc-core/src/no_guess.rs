@@ -0,0 +1,205 @@
+use web_time::Instant;
+use rand::prelude::*;
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::game::Neighborhood;
+use crate::puzzle::techniques::MineLogic;
+
+/// How a no-guess board came about
+#[derive(Clone, Copy, Debug)]
+pub struct NoGuessReport {
+    pub attempts: usize,
+    /// False when every attempt needed a guess, in which case the last board is used anyway
+    pub solvable: bool,
+    pub elapsed: Duration,
+}
+
+/// More mines asked for than there are cells outside the opening to hold them
+#[derive(Clone, Copy, Debug, Error)]
+#[error("{mines} mines don't fit in the {free} cells left outside the opening")]
+pub struct TooManyMines {
+    pub mines: usize,
+    pub free: usize,
+}
+
+/// Places mines outside the 3x3 around `start`, redrawing until deduction alone can clear the board from there or `max_attempts` run out
+pub fn generate(
+    width: usize,
+    height: usize,
+    mine_count: usize,
+    start: (usize, usize),
+    max_attempts: usize,
+    rng: &mut impl Rng,
+) -> Result<(Vec<Vec<bool>>, NoGuessReport), TooManyMines> {
+    let started = Instant::now();
+    let (x, y) = start;
+    let clear = (y.saturating_sub(1)..=usize::min(y + 1, height - 1))
+        .flat_map(|ny| (x.saturating_sub(1)..=usize::min(x + 1, width - 1)).map(move |nx| (nx, ny)))
+        .collect::<Vec<_>>();
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let mines = place_mines(width, height, mine_count, &clear, rng)?;
+        let solvable = solves(&mines, start);
+        if solvable || attempts >= max_attempts {
+            return Ok((mines, NoGuessReport { attempts, solvable, elapsed: started.elapsed() }));
+        }
+    }
+}
+
+/// Scatters `mine_count` mines over the cells not in `clear`, as `mines[y][x]`
+pub fn place_mines(
+    width: usize,
+    height: usize,
+    mine_count: usize,
+    clear: &[(usize, usize)],
+    rng: &mut impl Rng,
+) -> Result<Vec<Vec<bool>>, TooManyMines> {
+    let mut mines = vec![vec![false; width]; height];
+    let free = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|pos| !clear.contains(pos))
+        .collect::<Vec<_>>();
+    if free.len() < mine_count {
+        return Err(TooManyMines { mines: mine_count, free: free.len() });
+    }
+    for &(x, y) in free.choose_multiple(rng, mine_count) {
+        mines[y][x] = true;
+    }
+    Ok(mines)
+}
+
+/// Whether a player who never guesses can open every safe cell after clicking `start`
+pub fn solves(mines: &[Vec<bool>], start: (usize, usize)) -> bool {
+    let mut logic = MineLogic::new(mines);
+    if logic.is_mine(start) { return false }
+    logic.open(start);
+    while logic.step().is_some() {}
+    logic.is_solved()
+}
+
+/// Some mine layout `mines[y][x]` that agrees with the numbers `clues[y][x]`, mining only cells next to a number
+pub fn mines_for(clues: &[Vec<Option<u8>>]) -> Option<Vec<Vec<bool>>> {
+    let (width, height) = (clues.first().map_or(0, Vec::len), clues.len());
+    let around = |(x, y): (usize, usize)| Neighborhood::Moore.neighbors(x, y, width, height);
+    let candidates = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|&(x, y)| clues[y][x].is_none() && around((x, y)).iter().any(|&(nx, ny)| clues[ny][nx].is_some()))
+        .collect::<Vec<_>>();
+    // Each number's mines still to place and undecided candidates still around it
+    let mut wanted = clues.iter().map(|row| row.iter().map(|clue| clue.map_or(0, usize::from)).collect()).collect::<Vec<Vec<_>>>();
+    let mut open = vec![vec![0; width]; height];
+    for &pos in candidates.iter() {
+        for (x, y) in around(pos) {
+            open[y][x] += 1;
+        }
+    }
+    let fits = |wanted: &[Vec<usize>], open: &[Vec<usize>]| (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .all(|(x, y)| clues[y][x].is_none() || wanted[y][x] <= open[y][x]);
+    if !fits(&wanted, &open) { return None }
+    let mut mines = vec![vec![false; width]; height];
+    place_around(&candidates, &around, clues, &mut wanted, &mut open, &mut mines).then_some(mines)
+}
+
+// Decides the first of `candidates` each way, backing out as soon as some number can no longer be met
+fn place_around(
+    candidates: &[(usize, usize)],
+    around: &impl Fn((usize, usize)) -> Vec<(usize, usize)>,
+    clues: &[Vec<Option<u8>>],
+    wanted: &mut [Vec<usize>],
+    open: &mut [Vec<usize>],
+    mines: &mut [Vec<bool>],
+) -> bool {
+    let Some((&pos, rest)) = candidates.split_first() else {
+        return true;
+    };
+    let numbers = around(pos).into_iter().filter(|&(x, y)| clues[y][x].is_some()).collect::<Vec<_>>();
+    for mine in [true, false] {
+        let possible = numbers.iter().all(|&(x, y)| if mine { wanted[y][x] > 0 } else { wanted[y][x] < open[y][x] });
+        if !possible { continue }
+        for &(x, y) in numbers.iter() {
+            open[y][x] -= 1;
+            wanted[y][x] -= mine as usize;
+        }
+        mines[pos.1][pos.0] = mine;
+        if place_around(rest, around, clues, wanted, open, mines) {
+            return true;
+        }
+        for &(x, y) in numbers.iter() {
+            open[y][x] += 1;
+            wanted[y][x] += mine as usize;
+        }
+        mines[pos.1][pos.0] = false;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+
+    // `*` is a mine
+    fn mines(rows: &[&str]) -> Vec<Vec<bool>> {
+        rows.iter().map(|row| row.chars().map(|c| c == '*').collect()).collect()
+    }
+
+    #[test]
+    fn single_numbers_and_mine_count() {
+        // The 1 next to the opening gives the mine away, and then no mines are left for the last cell
+        assert!(solves(&mines(&["..*."]), (0, 0)));
+    }
+
+    #[test]
+    fn subset() {
+        // The corner 1s each see two of the three cells the middle 1 sees, which clears the outer cells
+        assert!(solves(&mines(&["...", "...", ".*."]), (0, 0)));
+    }
+
+    #[test]
+    fn fifty_fifty_needs_a_guess() {
+        assert!(!solves(&mines(&["..", "..", "*.", ".."]), (0, 0)));
+    }
+
+    #[test]
+    fn mines_agree_with_numbers() {
+        let clues = vec![vec![Some(1), None, None], vec![None, Some(2), None], vec![None, None, Some(1)]];
+        let mines = mines_for(&clues).unwrap();
+        for (x, y) in [(0, 0), (1, 1), (2, 2)] {
+            let count = Neighborhood::Moore.neighbors(x, y, 3, 3).iter().filter(|&&(nx, ny)| mines[ny][nx]).count();
+            assert_eq!(Some(count as u8), clues[y][x]);
+        }
+        assert!(mines_for(&[vec![Some(2), None]]).is_none());
+    }
+
+    #[test]
+    fn opening_is_clear() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            let (mines, _) = generate(10, 10, 40, (0, 5), 1, &mut rng).unwrap();
+            assert_eq!(mines.iter().flatten().filter(|&&mine| mine).count(), 40);
+            assert!((0..=1).all(|x| (4..=6).all(|y| !mines[y][x])));
+        }
+    }
+
+    #[test]
+    fn mines_that_dont_fit_are_errors() {
+        let mut rng = StdRng::seed_from_u64(5);
+        assert!(matches!(generate(3, 3, 9, (1, 1), 1, &mut rng), Err(TooManyMines { mines: 9, free: 0 })));
+        // A corner opening clears only four cells
+        assert!(generate(3, 3, 5, (0, 0), 1, &mut rng).is_ok());
+        assert!(matches!(place_mines(2, 2, 4, &[(0, 0)], &mut rng), Err(TooManyMines { free: 3, .. })));
+    }
+
+    #[test]
+    fn generated_boards_need_no_guess() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..5 {
+            let (mines, report) = generate(10, 10, 15, (4, 4), 1000, &mut rng).unwrap();
+            assert!(report.solvable);
+            assert!(solves(&mines, (4, 4)));
+        }
+    }
+}
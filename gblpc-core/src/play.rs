@@ -0,0 +1,252 @@
+use crate::game::*;
+use crate::history::*;
+use crate::rules::*;
+
+/// Which of a noun's actions a click runs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Click {
+    Left,
+    Right,
+}
+
+/// A board being played, `cells[y][x]`, with every step taken on it
+#[derive(Clone, Debug, Default)]
+pub struct Play {
+    pub cells: Vec<Vec<NounID>>,
+    pub history: History,
+}
+impl Play {
+    pub fn new(cells: Vec<Vec<NounID>>) -> Self {
+        Self { cells, history: History::default() }
+    }
+
+    /// Runs the clicked noun's action, if it has one for the click, returning whether it did
+    pub fn click(&mut self, game: &Game, x: usize, y: usize, click: Click) -> bool {
+        let noun = &game.nouns.nouns[self.cells[y][x]];
+        let action = match click {
+            Click::Left => noun.l_click(),
+            Click::Right => noun.r_click(),
+        };
+        let Some(action) = action.cloned() else { return false };
+        self.act(game, &action, x, y);
+        true
+    }
+
+    /// Applies the action as one undoable step, noting whether the board still breaks no rule
+    pub fn act(&mut self, game: &Game, action: &Action, x: usize, y: usize) {
+        self.apply(game, action, x, y);
+        let correct = game.mistake(&self.cells).is_none();
+        self.history.commit(correct);
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some(step) = self.history.undo() else { return false };
+        for change in step.changes.iter().rev() {
+            self.cells[change.y][change.x] = change.from;
+        }
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some(step) = self.history.redo() else { return false };
+        for change in step.changes.iter() {
+            self.cells[change.y][change.x] = change.to;
+        }
+        true
+    }
+
+    /// Undoes every step since the board last broke no rule
+    pub fn undo_to_correct(&mut self) {
+        for _ in 0..self.history.since_correct() {
+            self.undo();
+        }
+    }
+
+    /// The board and its steps with every noun looked up by name in `new`, a rebuild of `old`, or `None` once the
+    /// board changed shape. Nouns `new` no longer has become its default.
+    pub fn carried_over(&self, old: &Game, new: &Game) -> Option<Self> {
+        if (old.width, old.height, &old.regions) != (new.width, new.height, &new.regions) { return None }
+        let renumber = |noun_id: NounID| {
+            new.nouns.noun_map.get(old.nouns.nouns[noun_id].name()).copied().unwrap_or(new.nouns.gen_config.default)
+        };
+        let mut play = self.clone();
+        play.cells.iter_mut().flatten().for_each(|noun_id| *noun_id = renumber(*noun_id));
+        play.history.renumber(renumber);
+        Some(play)
+    }
+
+    /// The board as it was before any step was taken
+    pub fn start(&self) -> Vec<Vec<NounID>> {
+        let mut start = self.clone();
+        while start.undo() {}
+        start.cells
+    }
+
+    // Every write to the cells goes through here so the history sees it
+    fn set(&mut self, x: usize, y: usize, noun_id: NounID) {
+        let from = self.cells[y][x];
+        if from == noun_id { return }
+        self.cells[y][x] = noun_id;
+        self.history.record(Change { x, y, from, to: noun_id });
+    }
+
+    fn neighbors(&self, x: usize, y: usize, neighborhood: Neighborhood) -> Vec<(usize, usize)> {
+        neighborhood.neighbors(x, y, self.width(), self.height())
+    }
+
+    fn apply(&mut self, game: &Game, action: &Action, x: usize, y: usize) {
+        let current = self.cells[y][x];
+        match action {
+            &Action::TransformTo(next) => self.set(x, y, next),
+            Action::CycleThrough(cycle) => {
+                let next = match cycle.iter().position(|&noun_id| noun_id == current) {
+                    Some(index) => cycle[(index + 1) % cycle.len()],
+                    None => cycle[0],
+                };
+                self.set(x, y, next);
+            },
+            &Action::ToggleBetween(a, b) => {
+                self.set(x, y, if current == a { b } else { a });
+            },
+            Action::Reveal => {
+                self.reveal(game, x, y);
+            },
+            &Action::FloodReveal(neighborhood) => self.flood_reveal(game, x, y, neighborhood),
+            Action::Chord { count, flags, neighborhood } => {
+                let neighbors = self.neighbors(x, y, *neighborhood);
+                let flagged = neighbors.iter()
+                    .filter(|&&(nx, ny)| flags.contains(&self.cells[ny][nx]))
+                    .count();
+                if flagged != *count { return }
+                for (nx, ny) in neighbors {
+                    self.flood_reveal(game, nx, ny, *neighborhood);
+                }
+            },
+            Action::Sequence(actions) => {
+                for action in actions.iter() {
+                    self.apply(game, action, x, y);
+                }
+            },
+        }
+    }
+
+    /// Turns the cell into what its noun reveals as, returning how many counted nouns were around it
+    fn reveal(&mut self, game: &Game, x: usize, y: usize) -> Option<usize> {
+        let Reveal { count, into, neighborhood } = game.nouns.nouns[self.cells[y][x]].reveal()?;
+        let around = self.neighbors(x, y, *neighborhood).into_iter()
+            .filter(|&(nx, ny)| count.contains(&self.cells[ny][nx]))
+            .count();
+        let next = into[around.min(into.len() - 1)];
+        // A reveal that counts nothing, e.g. a mine exploding, must never start a flood
+        let around = if count.is_empty() { None } else { Some(around) };
+        self.set(x, y, next);
+        around
+    }
+
+    /// Reveals the cell and keeps revealing outwards from every cell with nothing counted around it
+    fn flood_reveal(&mut self, game: &Game, x: usize, y: usize, neighborhood: Neighborhood) {
+        let mut visited = vec![vec![false; self.width()]; self.height()];
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            if visited[y][x] { continue }
+            visited[y][x] = true;
+            if self.reveal(game, x, y) != Some(0) { continue }
+            stack.extend(self.neighbors(x, y, neighborhood));
+        }
+    }
+}
+impl Board for Play {
+    fn width(&self) -> usize {
+        self.cells.width()
+    }
+
+    fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    fn noun(&self, x: usize, y: usize) -> NounID {
+        self.cells[y][x]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const HIDDEN: NounID = 0;
+    const MINE: NounID = 1;
+    const FLAG: NounID = 2;
+
+    fn cell(name: &str, l_click: Option<Action>, r_click: Option<Action>, reveal: Option<Reveal>) -> Noun {
+        Noun::SpatialElement(SpatialElement::Cell { name: name.to_string(), l_click, r_click, reveal, blank: false, counts_as: None })
+    }
+
+    // Hidden cells open into Open0..Open8 by the mines around them, and a hidden mine stays put
+    fn minesweeper() -> Game {
+        let mut game = Game { width: 3, height: 3, ..Default::default() };
+        let open = (0..=8).map(|n| 3 + n).collect::<Vec<_>>();
+        let reveal = Reveal { count: vec![MINE], into: open, neighborhood: Neighborhood::Moore };
+        game.nouns.nouns = vec![
+            cell("Hidden", Some(Action::FloodReveal(Neighborhood::Moore)), Some(Action::TransformTo(FLAG)), Some(reveal)),
+            cell("Mine", None, Some(Action::TransformTo(FLAG)), None),
+            cell("Flag", None, Some(Action::TransformTo(HIDDEN)), None),
+        ];
+        game.nouns.nouns.extend((0..=8).map(|n| cell(&format!("Open{n}"), None, None, None)));
+        game.nouns.noun_map = names(&game.nouns.nouns);
+        game
+    }
+
+    fn names(nouns: &[Noun]) -> HashMap<String, NounID> {
+        nouns.iter().enumerate().map(|(noun_id, noun)| (noun.name().to_string(), noun_id)).collect()
+    }
+
+    fn by_name<'a>(game: &'a Game, cells: &[Vec<NounID>]) -> Vec<Vec<&'a str>> {
+        cells.iter().map(|row| row.iter().map(|&noun_id| game.nouns.nouns[noun_id].name()).collect()).collect()
+    }
+
+    #[test]
+    fn floods_and_undoes_in_one_step() {
+        let game = minesweeper();
+        let mut play = Play::new(vec![vec![HIDDEN, HIDDEN, HIDDEN], vec![HIDDEN, HIDDEN, HIDDEN], vec![HIDDEN, HIDDEN, MINE]]);
+        assert!(play.click(&game, 0, 0, Click::Left));
+        assert_eq!(play.cells, vec![vec![3, 3, 3], vec![3, 4, 4], vec![3, 4, MINE]]);
+        assert!(!play.click(&game, 0, 0, Click::Left));
+        assert!(play.undo());
+        assert!(play.cells.iter().flatten().filter(|&&noun_id| noun_id == HIDDEN).count() == 8);
+        assert!(play.redo());
+        assert_eq!(play.cells[1][1], 4);
+    }
+
+    #[test]
+    fn reloading_mid_game_keeps_every_step() {
+        let old = minesweeper();
+        let mut play = Play::new(vec![vec![HIDDEN, HIDDEN, HIDDEN], vec![HIDDEN, HIDDEN, HIDDEN], vec![HIDDEN, HIDDEN, MINE]]);
+        play.click(&old, 2, 2, Click::Right);
+        play.click(&old, 0, 0, Click::Left);
+        // The rebuilt game lists its flag first
+        let mut new = minesweeper();
+        new.nouns.nouns.rotate_left(FLAG);
+        new.nouns.noun_map = names(&new.nouns.nouns);
+        let mut carried = play.carried_over(&old, &new).unwrap();
+        assert_eq!(by_name(&new, &carried.cells), by_name(&old, &play.cells));
+        assert_eq!(by_name(&new, &carried.start()), by_name(&old, &play.start()));
+        assert!(!carried.history.is_empty());
+        assert!(carried.undo() && carried.undo());
+        assert_eq!(by_name(&new, &carried.cells), by_name(&old, &play.start()));
+
+        new.width = 4;
+        assert!(play.carried_over(&old, &new).is_none());
+    }
+
+    #[test]
+    fn right_click_runs_its_own_action() {
+        let game = minesweeper();
+        let mut play = Play::new(vec![vec![HIDDEN; 3]; 3]);
+        play.click(&game, 2, 2, Click::Right);
+        assert_eq!(play.cells[2][2], FLAG);
+        play.click(&game, 2, 2, Click::Right);
+        assert_eq!(play.cells[2][2], HIDDEN);
+        assert_eq!(play.history.since_correct(), 0);
+    }
+}
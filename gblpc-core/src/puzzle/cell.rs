@@ -0,0 +1,18 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+pub trait Cell: Clone + Send + Sync + 'static {
+    type CellVariant: PartialEq;
+    type CellState: Clone + Debug + Eq + Hash;
+    /// A hidden cell at `(row, col)`, before any state is decided
+    fn new(row: usize, col: usize) -> Self;
+    fn get_pos(&self) -> (usize, usize);
+    fn get_variant(&self) -> &Self::CellVariant;
+    fn get_state(&self) -> &Self::CellState;
+    fn set_state(&mut self, state: Self::CellState);
+    /// Every state the solver may try while this cell is hidden
+    fn candidate_states(&self) -> Vec<Self::CellState>;
+    fn is_revealed(&self) -> bool;
+    fn reveal(&mut self);
+    fn hide(&mut self);
+}
@@ -0,0 +1,325 @@
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+use super::cell::Cell;
+use super::difficulty::{Difficulty, Rating};
+use super::grid::Grid;
+use super::solver::Solution;
+
+pub struct PuzzleRule<C: Cell> {
+    pub description: String,
+    /// Checked against partly solved grids too, so it must only fail once the revealed cells break it
+    pub condition: Box<dyn Fn(&Grid<C>) -> bool>,
+}
+
+pub struct PuzzleDefinition<C: Cell> {
+    pub rows: usize,
+    pub cols: usize,
+    pub rules: Vec<PuzzleRule<C>>,
+}
+
+/// Which givens must be hidden together, so the remaining clues keep the pattern
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Symmetry {
+    #[default]
+    None,
+    /// 180 degrees about the centre
+    Rotational,
+    /// Left to right
+    Mirror,
+}
+impl Symmetry {
+    fn partner(&self, row: usize, col: usize, rows: usize, cols: usize) -> (usize, usize) {
+        match self {
+            Self::None => (row, col),
+            Self::Rotational => (rows - 1 - row, cols - 1 - col),
+            Self::Mirror => (row, cols - 1 - col),
+        }
+    }
+
+    /// Groups every cell with its symmetric partner, each cell appearing once
+    pub fn orbits(&self, rows: usize, cols: usize) -> Vec<Vec<(usize, usize)>> {
+        let mut orbits = Vec::new();
+        for row in 0..rows {
+            for col in 0..cols {
+                let partner = self.partner(row, col, rows, cols);
+                if partner < (row, col) { continue }
+                if partner == (row, col) {
+                    orbits.push(vec![(row, col)]);
+                } else {
+                    orbits.push(vec![(row, col), partner]);
+                }
+            }
+        }
+        orbits
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenerateOptions {
+    pub symmetry: Symmetry,
+    /// Stop hiding givens once this many are left, or dig as far as uniqueness allows when `None`
+    pub target_clues: Option<usize>,
+}
+
+// Seeds tried after `seed` before giving up on a difficulty
+const RATED_ATTEMPTS: u64 = 50;
+
+impl<C: Cell> PuzzleDefinition<C> {
+    pub fn validate(&self, grid: &Grid<C>) -> bool {
+        self.rules.iter().all(|rule| (rule.condition)(grid))
+    }
+
+    pub fn generate_puzzle(&self, seed: u64) -> Option<Grid<C>> {
+        self.generate_puzzle_with(seed, GenerateOptions::default())
+    }
+
+    /// Generates a full solution for `seed`, then hides givens while the solver still finds it unique
+    pub fn generate_puzzle_with(&self, seed: u64, options: GenerateOptions) -> Option<Grid<C>> {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut grid = self.generate_full_solution(&mut rng)?;
+        self.reduce_revealed_cells(&mut grid, &mut rng, options, |_| true);
+        Some(grid)
+    }
+
+    /// Like `generate_puzzle_with`, but only hides givens while `rate` stays within `difficulty`, moving on to the next seeds until a puzzle lands on it exactly
+    pub fn generate_puzzle_rated(
+        &self,
+        seed: u64,
+        options: GenerateOptions,
+        difficulty: Difficulty,
+        rate: impl Fn(&Grid<C>) -> Rating,
+    ) -> Option<Grid<C>> {
+        (0..RATED_ATTEMPTS).find_map(|attempt| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed.wrapping_add(attempt));
+            let mut grid = self.generate_full_solution(&mut rng)?;
+            self.reduce_revealed_cells(&mut grid, &mut rng, options, |grid| rate(grid).difficulty() <= difficulty);
+            (rate(&grid).difficulty() == difficulty).then_some(grid)
+        })
+    }
+
+    fn generate_full_solution(&self, rng: &mut ChaCha8Rng) -> Option<Grid<C>> {
+        let empty = Grid::new(
+            self.rows,
+            self.cols,
+            (0..self.rows).map(|row| (0..self.cols).map(|col| C::new(row, col)).collect()).collect(),
+        );
+        // A shuffled search turns up a different full solution for every seed
+        let mut solutions = Vec::new();
+        self.search(&mut empty.clone(), 1, &mut Some(rng), &mut solutions);
+        solutions.pop()
+    }
+
+    /// Hides symmetric groups of givens in a shuffled order, keeping any group whose removal loses uniqueness, undershoots the target or fails `accept`
+    fn reduce_revealed_cells(&self, grid: &mut Grid<C>, rng: &mut ChaCha8Rng, options: GenerateOptions, accept: impl Fn(&Grid<C>) -> bool) {
+        let mut orbits = options.symmetry.orbits(grid.rows, grid.cols);
+        orbits.shuffle(rng);
+
+        let target = options.target_clues.unwrap_or(0);
+        let mut clues = grid.clue_count();
+        for orbit in orbits {
+            if clues <= target { break }
+            if clues - orbit.len() < target { continue }
+            set_revealed(grid, &orbit, false);
+            if self.has_unique_solution(grid) && accept(grid) {
+                clues -= orbit.len();
+            } else {
+                set_revealed(grid, &orbit, true);
+            }
+        }
+    }
+
+    pub fn has_unique_solution(&self, grid: &Grid<C>) -> bool {
+        matches!(self.solve(grid), Solution::Unique(_))
+    }
+}
+
+fn set_revealed<C: Cell>(grid: &mut Grid<C>, cells: &[(usize, usize)], revealed: bool) {
+    for &(row, col) in cells {
+        if let Some(cell) = grid.get_cell_mut(row, col) {
+            if revealed { cell.reveal() } else { cell.hide() }
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! define_puzzle_rules {
+    ($name:ident {
+        $($rule_name:ident($cell:ident: &$cell_type:ty) => $rule_body:expr),*$(,)?
+    }) => {
+        struct $name;
+        impl $name {
+            $(
+                fn $rule_name($cell: &$cell_type, grid: &Grid<$cell_type>) -> bool {
+                    $rule_body
+                }
+            )*
+        }
+    };
+}
+
+// // Example usage of the macro
+// define_puzzle_rules! {
+//     SudokuRules {
+//         no_duplicates_in_row(row: usize) => {
+//             let mut seen = HashSet::new();
+//             for col in 0..grid.cols {
+//                 if let Some(cell) = grid.get_cell(row, col) {
+//                     if !seen.insert(cell.get_state()) {
+//                         return false;
+//                     }
+//                 }
+//             }
+//             true
+//         },
+//         no_duplicates_in_column(col: usize) => {
+//             let mut seen = HashSet::new();
+//             for row in 0..grid.rows {
+//                 if let Some(cell) = grid.get_cell(row, col) {
+//                     if !seen.insert(cell.get_state()) {
+//                         return false;
+//                     }
+//                 }
+//             }
+//             true
+//         },
+//         no_duplicates_in_3x3(start_row: usize, start_col: usize) => {
+//             let mut seen = HashSet::new();
+//             for row in start_row..start_row + 3 {
+//                 for col in start_col..start_col + 3 {
+//                     if let Some(cell) = grid.get_cell(row, col) {
+//                         if !seen.insert(cell.get_state()) {
+//                             return false;
+//                         }
+//                     }
+//                 }
+//             }
+//             true
+//         },
+//     }
+// }
+
+// // Example of how to use the defined rules
+// fn check_sudoku<C: Cell>(grid: &Grid<C>) -> bool {
+//     // Check rows and columns
+//     for i in 0..9 {
+//         if !SudokuRules::no_duplicates_in_row(i, grid) || !SudokuRules::no_duplicates_in_column(i, grid) {
+//             return false;
+//         }
+//     }
+
+//     // Check 3x3 sub-grids
+//     for i in (0..9).step_by(3) {
+//         for j in (0..9).step_by(3) {
+//             if !SudokuRules::no_duplicates_in_3x3(i, j, grid) {
+//                 return false;
+//             }
+//         }
+//     }
+
+//     true
+// }
+
+// Example usage:
+// define_puzzle_rules! {
+//     SudokuRules {
+//         check_row(cell: &SudokuCell) => {
+//             // Implementation of row checking logic...
+//         },
+//         check_column(cell: &SudokuCell) => {
+//             // Implementation of column checking logic...
+//         },
+//         // More rules...
+//     }
+// }
+//
+// let sudoku_puzzle = PuzzleDefinition {
+//     rows: 9,
+//     cols: 9,
+//     rules: vec![
+//         PuzzleRule {
+//             description: "Each row must contain digits 1-9 without repetition".to_string(),
+//             condition: Box::new(|grid| SudokuRules::check_row(grid)),
+//         },
+//         // More rules...
+//     ],
+// };
+//
+// let generated_puzzle = sudoku_puzzle.generate_puzzle(12345);
+// let is_valid = sudoku_puzzle.validate(&generated_puzzle);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::difficulty::rate_sudoku;
+    use super::super::solver::tests::{latin_square, LatinCell};
+
+    fn revealed(grid: &Grid<LatinCell>, row: usize, col: usize) -> bool {
+        grid.get_cell(row, col).unwrap().is_revealed()
+    }
+
+    #[test]
+    fn generated_puzzle_is_unique() {
+        let definition = latin_square(3);
+        let puzzle = definition.generate_puzzle(7).unwrap();
+        assert!(definition.has_unique_solution(&puzzle));
+        assert!(puzzle.clue_count() < 9);
+    }
+
+    #[test]
+    fn targets_a_difficulty() {
+        let rate = |grid: &Grid<LatinCell>| {
+            let values = grid.cells.iter()
+                .map(|row| row.iter().map(|cell| cell.is_revealed().then_some(*cell.get_state())).collect())
+                .collect();
+            let units = (0..3).flat_map(|i| [(0..3).map(|x| (x, i)).collect(), (0..3).map(|y| (i, y)).collect()]).collect();
+            rate_sudoku(values, units)
+        };
+        let definition = latin_square(3);
+        let puzzle = definition.generate_puzzle_rated(5, GenerateOptions::default(), Difficulty::Easy, rate).unwrap();
+        assert_eq!(rate(&puzzle).difficulty(), Difficulty::Easy);
+        assert!(definition.has_unique_solution(&puzzle));
+    }
+
+    #[test]
+    fn same_seed_same_puzzle() {
+        let definition = latin_square(3);
+        let values = |grid: Grid<LatinCell>| grid.cells.iter().flatten().map(|cell| (*cell.get_state(), cell.is_revealed())).collect::<Vec<_>>();
+        assert_eq!(values(definition.generate_puzzle(42).unwrap()), values(definition.generate_puzzle(42).unwrap()));
+    }
+
+    #[test]
+    fn rotational_symmetry() {
+        let options = GenerateOptions { symmetry: Symmetry::Rotational, target_clues: None };
+        let puzzle = latin_square(3).generate_puzzle_with(3, options).unwrap();
+        for (row, col) in (0..3).flat_map(|row| (0..3).map(move |col| (row, col))) {
+            assert_eq!(revealed(&puzzle, row, col), revealed(&puzzle, 2 - row, 2 - col));
+        }
+    }
+
+    #[test]
+    fn mirror_symmetry() {
+        let options = GenerateOptions { symmetry: Symmetry::Mirror, target_clues: None };
+        let puzzle = latin_square(3).generate_puzzle_with(5, options).unwrap();
+        for (row, col) in (0..3).flat_map(|row| (0..3).map(move |col| (row, col))) {
+            assert_eq!(revealed(&puzzle, row, col), revealed(&puzzle, row, 2 - col));
+        }
+    }
+
+    #[test]
+    fn stops_at_target_clues() {
+        let definition = latin_square(3);
+        let options = GenerateOptions { symmetry: Symmetry::None, target_clues: Some(6) };
+        let puzzle = definition.generate_puzzle_with(11, options).unwrap();
+        assert_eq!(puzzle.clue_count(), 6);
+        assert!(definition.has_unique_solution(&puzzle));
+    }
+
+    #[test]
+    fn orbits_cover_every_cell_once() {
+        for symmetry in [Symmetry::None, Symmetry::Rotational, Symmetry::Mirror] {
+            let mut cells = symmetry.orbits(3, 4).concat();
+            cells.sort();
+            assert_eq!(cells, (0..3).flat_map(|row| (0..4).map(move |col| (row, col))).collect::<Vec<_>>());
+        }
+    }
+}
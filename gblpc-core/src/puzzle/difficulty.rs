@@ -0,0 +1,220 @@
+use serde::Serialize;
+
+use super::dsl::{self, CellContent, PuzzleType, Rule};
+use super::techniques::{MineLogic, NonogramLogic, SudokuLogic, TentsLogic, Technique};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    /// Needs more than the techniques we know, usually a guess
+    Fiendish,
+}
+
+impl Difficulty {
+    /// The tier of a hint that had to read `places` rows, columns or regions together, for games rated by their hints
+    /// rather than by techniques. It follows the techniques' tiers: a single reads one place, a pair or a clue subset
+    /// two, and an X-Wing more, though a hint can't tell a naked single's row and column from a pair's two places.
+    pub fn of_places(places: usize) -> Self {
+        match places {
+            0 | 1 => Self::Easy,
+            2 => Self::Medium,
+            _ => Self::Hard,
+        }
+    }
+}
+
+impl Technique {
+    pub fn difficulty(&self) -> Difficulty {
+        match self {
+            Self::NakedSingle | Self::HiddenSingle | Self::SingleClue | Self::NoTreeNearby | Self::TentCount | Self::LineOverlap => {
+                Difficulty::Easy
+            },
+            Self::NakedPair | Self::HiddenPair | Self::ClueSubset | Self::MineCount | Self::LoneTree | Self::LineLogic => {
+                Difficulty::Medium
+            },
+            Self::XWing => Difficulty::Hard,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Rating {
+    /// How many steps used each technique, in the order they were first needed
+    pub techniques: Vec<(Technique, usize)>,
+    pub solved: bool,
+}
+impl Rating {
+    fn record(&mut self, technique: Technique) {
+        match self.techniques.iter_mut().find(|(used, _)| *used == technique) {
+            Some((_, count)) => *count += 1,
+            None => self.techniques.push((technique, 1)),
+        }
+    }
+
+    /// The tier of the hardest technique needed, or `Fiendish` when they weren't enough
+    pub fn difficulty(&self) -> Difficulty {
+        if !self.solved { return Difficulty::Fiendish }
+        self.techniques.iter()
+            .map(|(technique, _)| technique.difficulty())
+            .max()
+            .unwrap_or(Difficulty::Easy)
+    }
+}
+
+/// Rates givens `values[y][x]` where every unit may hold each digit only once
+pub fn rate_sudoku(values: Vec<Vec<Option<u8>>>, units: Vec<Vec<(usize, usize)>>) -> Rating {
+    let mut logic = SudokuLogic::new(values, units);
+    let mut rating = Rating::default();
+    while let Some(technique) = logic.step() {
+        rating.record(technique);
+    }
+    rating.solved = logic.is_solved();
+    rating
+}
+
+/// Rates a Minesweeper layout `mines[y][x]` from the cells already open
+pub fn rate_minesweeper(mines: &[Vec<bool>], opened: &[(usize, usize)]) -> Rating {
+    let mut logic = MineLogic::new(mines);
+    for &pos in opened.iter() {
+        logic.open(pos);
+    }
+    let mut rating = Rating::default();
+    while let Some(technique) = logic.step() {
+        rating.record(technique);
+    }
+    rating.solved = logic.is_solved();
+    rating
+}
+
+/// Rates Tents and Trees from its trees `trees[y][x]` and the tent counts of its rows and columns
+pub fn rate_tents(trees: Vec<Vec<bool>>, rows: Vec<Option<usize>>, columns: Vec<Option<usize>>) -> Rating {
+    let mut logic = TentsLogic::new(trees, rows, columns);
+    let mut rating = Rating::default();
+    while let Some(technique) = logic.step() {
+        rating.record(technique);
+    }
+    rating.solved = logic.is_solved();
+    rating
+}
+
+/// Rates a nonogram from the runs along its rows and columns
+pub fn rate_nonogram(rows: Vec<Vec<usize>>, columns: Vec<Vec<usize>>) -> Rating {
+    let mut logic = NonogramLogic::new(rows, columns);
+    let mut rating = Rating::default();
+    while let Some(technique) = logic.step() {
+        rating.record(technique);
+    }
+    rating.solved = logic.is_solved();
+    rating
+}
+
+impl dsl::PuzzleDefinition {
+    /// Rates the puzzle from its givens, or `None` for Aquarium and Crossword, which have no technique solver yet
+    pub fn rate(&self) -> Option<Rating> {
+        let dsl::Grid { width, height, .. } = self.puzzle.grid;
+        match self.puzzle.puzzle_type {
+            PuzzleType::Sudoku => {
+                let values = (0..height)
+                    .map(|y| (0..width).map(|x| match self.given(x, y) {
+                        Some(&CellContent::Number(number)) => Some(number),
+                        _ => None,
+                    }).collect())
+                    .collect();
+                let units = self.puzzle.rules.iter()
+                    .filter_map(|rule| match *rule {
+                        Rule::UniqueInRow(y) => Some((0..width).map(|x| (x, y)).collect()),
+                        Rule::UniqueInColumn(x) => Some((0..height).map(|y| (x, y)).collect()),
+                        Rule::UniqueInRegion(id) => self.puzzle.regions.iter()
+                            .find(|region| region.id == id)
+                            .map(|region| region.cells.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                Some(rate_sudoku(values, units))
+            },
+            PuzzleType::Minesweeper => {
+                let mines = (0..height)
+                    .map(|y| (0..width).map(|x| self.puzzle.grid.content(x, y) == Some(&CellContent::Mine)).collect())
+                    .collect::<Vec<Vec<bool>>>();
+                let opened = (0..height)
+                    .flat_map(|y| (0..width).map(move |x| (x, y)))
+                    .filter(|&(x, y)| !mines[y][x] && self.given(x, y).is_some())
+                    .collect::<Vec<_>>();
+                Some(rate_minesweeper(&mines, &opened))
+            },
+            PuzzleType::TentsAndTrees => {
+                let trees = (0..height)
+                    .map(|y| (0..width).map(|x| self.puzzle.grid.content(x, y) == Some(&CellContent::Tree)).collect())
+                    .collect();
+                let (mut rows, mut columns) = (vec![None; height], vec![None; width]);
+                for rule in self.puzzle.rules.iter() {
+                    match *rule {
+                        Rule::TentsInRow(y, count) if y < height => rows[y] = Some(usize::from(count)),
+                        Rule::TentsInColumn(x, count) if x < width => columns[x] = Some(usize::from(count)),
+                        _ => (),
+                    }
+                }
+                Some(rate_tents(trees, rows, columns))
+            },
+            PuzzleType::Nonogram => {
+                let (mut rows, mut columns) = (vec![Vec::new(); height], vec![Vec::new(); width]);
+                for rule in self.puzzle.rules.iter() {
+                    let (lines, index, runs) = match rule {
+                        Rule::RunsInRow(y, runs) => (&mut rows, *y, runs),
+                        Rule::RunsInColumn(x, runs) => (&mut columns, *x, runs),
+                        _ => continue,
+                    };
+                    if let Some(line) = lines.get_mut(index) {
+                        *line = runs.iter().map(|&run| usize::from(run)).collect();
+                    }
+                }
+                Some(rate_nonogram(rows, columns))
+            },
+            PuzzleType::Aquarium | PuzzleType::Crossword => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hardest_technique_sets_the_tier() {
+        let mut rating = Rating { solved: true, ..Default::default() };
+        assert_eq!(rating.difficulty(), Difficulty::Easy);
+        rating.record(Technique::HiddenSingle);
+        rating.record(Technique::XWing);
+        rating.record(Technique::NakedPair);
+        rating.record(Technique::HiddenSingle);
+        assert_eq!(rating.techniques, vec![(Technique::HiddenSingle, 2), (Technique::XWing, 1), (Technique::NakedPair, 1)]);
+        assert_eq!(rating.difficulty(), Difficulty::Hard);
+        rating.solved = false;
+        assert_eq!(rating.difficulty(), Difficulty::Fiendish);
+    }
+
+    #[test]
+    fn tents_and_nonograms_rate_from_links() {
+        // A tree in the corner of a 2x2 board with its tent below it
+        let tents = dsl::PuzzleDefinition::from_url("https://puzz.link/p?tents/2/2/10018").unwrap();
+        assert_eq!(tents.rate().unwrap().difficulty(), Difficulty::Easy);
+        let nonogram = dsl::PuzzleDefinition::from_url("https://puzz.link/p?nonogram/3/3/3011000201102").unwrap();
+        let rating = nonogram.rate().unwrap();
+        assert!(rating.solved);
+        assert_eq!(rating.techniques[0].0, Technique::LineOverlap);
+    }
+
+    #[test]
+    fn minesweeper_tiers() {
+        let counted = vec![vec![false, false, true, false]];
+        assert_eq!(rate_minesweeper(&counted, &[(0, 0)]).difficulty(), Difficulty::Medium);
+        let subset = vec![vec![false; 3], vec![false; 3], vec![false, true, false]];
+        assert_eq!(rate_minesweeper(&subset, &[(0, 0)]).difficulty(), Difficulty::Medium);
+        let easy = vec![vec![false; 3], vec![false; 3], vec![true, true, true]];
+        assert_eq!(rate_minesweeper(&easy, &[(0, 0)]).difficulty(), Difficulty::Easy);
+        let fifty_fifty = vec![vec![false; 2], vec![false; 2], vec![true, false], vec![false; 2]];
+        assert_eq!(rate_minesweeper(&fifty_fifty, &[(0, 0)]).difficulty(), Difficulty::Fiendish);
+    }
+}
@@ -1,12 +1,12 @@
 use std::ops::{BitAnd, BitOr, BitXor, Not};
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
     Left, Right, Up, Down, LeftUp, LeftDown, RightUp, RightDown
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct DirectionSet(u8);
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DirectionSet(u8);
 
 impl DirectionSet {
     const LEFT: u8 = 1 << 0;
@@ -18,11 +18,11 @@ impl DirectionSet {
     const RIGHT_UP: u8 = 1 << 6;
     const RIGHT_DOWN: u8 = 1 << 7;
 
-    fn new() -> Self {
+    pub fn new() -> Self {
         DirectionSet(0)
     }
 
-    fn from_directions(directions: &[Direction]) -> Self {
+    pub fn from_directions(directions: &[Direction]) -> Self {
         let mut set = 0;
         for &dir in directions {
             set |= match dir {
@@ -39,7 +39,7 @@ impl DirectionSet {
         DirectionSet(set)
     }
 
-    fn contains(&self, direction: Direction) -> bool {
+    pub fn contains(&self, direction: Direction) -> bool {
         let mask = match direction {
             Direction::Left => Self::LEFT,
             Direction::Right => Self::RIGHT,
@@ -0,0 +1,179 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PuzzleType {
+    Sudoku,
+    Minesweeper,
+    TentsAndTrees,
+    Aquarium,
+    Nonogram,
+    Crossword,
+}
+
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    pub puzzle_type: PuzzleType,
+    pub grid: Grid,
+    pub regions: Vec<Region>,
+    pub rules: Vec<Rule>,
+    pub cell_variants: Vec<CellVariant>,
+}
+impl Puzzle {
+    /// An empty `width` x `height` grid without regions or rules
+    pub fn new(puzzle_type: PuzzleType, width: usize, height: usize) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Cell { x, y, value: None, region_id: None }))
+            .collect();
+        Self {
+            puzzle_type,
+            grid: Grid { width, height, cells },
+            regions: Vec::new(),
+            rules: Vec::new(),
+            cell_variants: Vec::new(),
+        }
+    }
+
+    /// An empty `size` x `size` sudoku with its rows, columns and blocks unique
+    pub fn sudoku(size: usize) -> Self {
+        let mut puzzle = Self::new(PuzzleType::Sudoku, size, size);
+        let (block_width, block_height) = sudoku_block(size);
+        let blocks_across = size / block_width;
+        for id in 0..size {
+            let (left, top) = (id % blocks_across * block_width, id / blocks_across * block_height);
+            let cells = (top..top + block_height)
+                .flat_map(|y| (left..left + block_width).map(move |x| (x, y)))
+                .collect();
+            puzzle.add_region(Region { id, cells });
+        }
+        puzzle.rules.extend((0..size).map(Rule::UniqueInRow));
+        puzzle.rules.extend((0..size).map(Rule::UniqueInColumn));
+        puzzle.rules.extend((0..size).map(Rule::UniqueInRegion));
+        puzzle
+    }
+
+    pub fn add_region(&mut self, region: Region) {
+        for &(x, y) in region.cells.iter() {
+            if let Some(cell) = self.grid.get_mut(x, y) {
+                cell.region_id = Some(region.id);
+            }
+        }
+        self.regions.push(region);
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, content: CellContent) {
+        if let Some(cell) = self.grid.get_mut(x, y) {
+            cell.value = Some(content);
+        }
+    }
+}
+
+// Blocks are as wide as they can be without outgrowing a square, e.g. 3x2 for a 6x6 sudoku
+fn sudoku_block(size: usize) -> (usize, usize) {
+    let height = (1..=size).filter(|height| height * height <= size && size.is_multiple_of(*height)).max().unwrap_or(1);
+    (size / height, height)
+}
+
+#[derive(Debug, Clone)]
+pub struct Grid {
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<Cell>,
+}
+impl Grid {
+    pub fn get(&self, x: usize, y: usize) -> Option<&Cell> {
+        self.cells.iter().find(|cell| cell.x == x && cell.y == y)
+    }
+
+    pub fn content(&self, x: usize, y: usize) -> Option<&CellContent> {
+        self.get(x, y).and_then(|cell| cell.value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut Cell> {
+        self.cells.iter_mut().find(|cell| cell.x == x && cell.y == y)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cell {
+    pub x: usize,
+    pub y: usize,
+    pub value: Option<CellContent>,
+    pub region_id: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CellContent {
+    Number(u8),
+    Symbol(char),
+    Custom(String),
+    Mine,
+    Tent,
+    Tree,
+    Water,
+    /// A shaded cell, e.g. in a nonogram
+    Filled,
+    Empty,
+}
+
+#[derive(Debug, Clone)]
+pub struct Region {
+    pub id: usize,
+    pub cells: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Rule {
+    UniqueInRow(usize),
+    UniqueInColumn(usize),
+    UniqueInRegion(usize),
+    SumEquals(Vec<(usize, usize)>, i32),
+    AdjacentMines(usize, usize, u8),
+    TotalMines(u32),
+    TentsInRow(usize, u8),
+    TentsInColumn(usize, u8),
+    TentTreePairing,
+    NoAdjacentTents,
+    WaterInRow(usize, u8),
+    WaterInColumn(usize, u8),
+    WaterFlowsUpward,
+    /// The lengths of the runs of filled cells along the row, in order
+    RunsInRow(usize, Vec<u8>),
+    RunsInColumn(usize, Vec<u8>),
+}
+
+#[derive(Debug, Clone)]
+pub struct CellVariant {
+    pub content: CellContent,
+    pub proportion: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct PuzzleDefinition {
+    pub puzzle: Puzzle,
+    pub initial_state: Vec<CellState>,
+}
+
+impl PuzzleDefinition {
+    pub fn new(puzzle: Puzzle) -> Self {
+        Self { puzzle, initial_state: Vec::new() }
+    }
+
+    /// What the player starts out seeing at a cell: the revealed initial state, or the grid itself when there's no initial state
+    pub fn given(&self, x: usize, y: usize) -> Option<&CellContent> {
+        if self.initial_state.is_empty() {
+            return self.puzzle.grid.content(x, y);
+        }
+        self.initial_state.iter()
+            .find(|state| state.x == x && state.y == y && state.revealed)
+            .map(|state| &state.content)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CellState {
+    pub x: usize,
+    pub y: usize,
+    pub content: CellContent,
+    pub revealed: bool,
+    pub flagged: bool,
+}
@@ -0,0 +1,372 @@
+use std::collections::HashSet;
+
+use super::dsl::{Rule, Grid, CellContent, Region};
+
+pub fn validate_rule(rule: &Rule, grid: &Grid, regions: &[Region]) -> bool {
+    match rule {
+        Rule::UniqueInRow(row) => validate_unique_in_row(*row, grid),
+        Rule::UniqueInColumn(col) => validate_unique_in_column(*col, grid),
+        Rule::UniqueInRegion(region_id) => validate_unique_in_region(*region_id, grid, regions),
+        Rule::SumEquals(cells, target_sum) => validate_sum_equals(cells, *target_sum, grid),
+        Rule::AdjacentMines(x, y, count) => validate_adjacent_mines(*x, *y, *count, grid),
+        Rule::TotalMines(count) => validate_total_mines(*count, grid),
+        Rule::TentsInRow(row, count) => validate_tents_in_row(*row, *count, grid),
+        Rule::TentsInColumn(col, count) => validate_tents_in_column(*col, *count, grid),
+        Rule::TentTreePairing => validate_tent_tree_pairing(grid),
+        Rule::NoAdjacentTents => validate_no_adjacent_tents(grid),
+        Rule::WaterInRow(row, count) => validate_water_in_row(*row, *count, grid),
+        Rule::WaterInColumn(col, count) => validate_water_in_column(*col, *count, grid),
+        Rule::WaterFlowsUpward => validate_water_flows_upward(grid, regions),
+        Rule::RunsInRow(row, runs) => validate_runs(grid, (0..grid.width).map(|x| (x, *row)), runs),
+        Rule::RunsInColumn(col, runs) => validate_runs(grid, (0..grid.height).map(|y| (*col, y)), runs),
+    }
+}
+
+fn all_unique<'a>(values: impl Iterator<Item = &'a CellContent>) -> bool {
+    let mut seen = HashSet::new();
+    values
+        .filter(|value| !matches!(value, CellContent::Empty))
+        .all(|value| seen.insert(value))
+}
+
+fn validate_unique_in_row(row: usize, grid: &Grid) -> bool {
+    all_unique(grid.cells
+        .iter()
+        .filter(|cell| cell.y == row)
+        .filter_map(|cell| cell.value.as_ref()))
+}
+
+fn validate_unique_in_column(col: usize, grid: &Grid) -> bool {
+    all_unique(grid.cells
+        .iter()
+        .filter(|cell| cell.x == col)
+        .filter_map(|cell| cell.value.as_ref()))
+}
+
+fn validate_unique_in_region(region_id: usize, grid: &Grid, regions: &[Region]) -> bool {
+    let Some(region) = regions.iter().find(|region| region.id == region_id) else { return false };
+    all_unique(region.cells
+        .iter()
+        .filter_map(|&(x, y)| grid.content(x, y)))
+}
+
+fn validate_sum_equals(cells: &[(usize, usize)], target_sum: i32, grid: &Grid) -> bool {
+    let sum: i32 = cells.iter()
+        .filter_map(|&(x, y)| match grid.content(x, y) {
+            Some(&CellContent::Number(number)) => Some(number as i32),
+            _ => None,
+        })
+        .sum();
+
+    sum == target_sum
+}
+
+fn neighbors(x: usize, y: usize, grid: &Grid) -> impl Iterator<Item = (usize, usize)> + '_ {
+    (-1..=1isize)
+        .flat_map(|dy| (-1..=1isize).map(move |dx| (dx, dy)))
+        .filter(|&offset| offset != (0, 0))
+        .filter_map(move |(dx, dy)| {
+            let ax = x.checked_add_signed(dx).filter(|&ax| ax < grid.width)?;
+            let ay = y.checked_add_signed(dy).filter(|&ay| ay < grid.height)?;
+            Some((ax, ay))
+        })
+}
+
+fn count_in_line(grid: &Grid, in_line: impl Fn(usize, usize) -> bool, content: CellContent) -> usize {
+    grid.cells.iter()
+        .filter(|cell| in_line(cell.x, cell.y) && cell.value.as_ref() == Some(&content))
+        .count()
+}
+
+fn validate_adjacent_mines(x: usize, y: usize, count: u8, grid: &Grid) -> bool {
+    let mine_count = neighbors(x, y, grid)
+        .filter(|&(ax, ay)| matches!(grid.content(ax, ay), Some(CellContent::Mine)))
+        .count();
+
+    mine_count == count as usize
+}
+
+fn validate_total_mines(count: u32, grid: &Grid) -> bool {
+    count_in_line(grid, |_, _| true, CellContent::Mine) == count as usize
+}
+
+fn validate_tents_in_row(row: usize, count: u8, grid: &Grid) -> bool {
+    count_in_line(grid, |_, y| y == row, CellContent::Tent) == count as usize
+}
+
+fn validate_tents_in_column(col: usize, count: u8, grid: &Grid) -> bool {
+    count_in_line(grid, |x, _| x == col, CellContent::Tent) == count as usize
+}
+
+fn validate_tent_tree_pairing(grid: &Grid) -> bool {
+    match_tents_to_trees(grid).is_perfect()
+}
+
+/// A maximum one-to-one pairing of trees with orthogonally adjacent tents
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TentTreeMatching {
+    /// `(tree, tent)` positions
+    pub pairs: Vec<((usize, usize), (usize, usize))>,
+    pub unmatched_trees: Vec<(usize, usize)>,
+    pub unmatched_tents: Vec<(usize, usize)>,
+}
+impl TentTreeMatching {
+    pub fn is_perfect(&self) -> bool {
+        self.unmatched_trees.is_empty() && self.unmatched_tents.is_empty()
+    }
+}
+
+/// Pairs trees and tents with augmenting paths, so an early pairing never blocks a later one
+pub fn match_tents_to_trees(grid: &Grid) -> TentTreeMatching {
+    let positions = |content: CellContent| grid.cells.iter()
+        .filter(|cell| cell.value.as_ref() == Some(&content))
+        .map(|cell| (cell.x, cell.y))
+        .collect::<Vec<_>>();
+    let orthogonal = |(tree_x, tree_y): (usize, usize), (tent_x, tent_y): (usize, usize)| {
+        tent_x.abs_diff(tree_x) + tent_y.abs_diff(tree_y) == 1
+    };
+    match_pairs(positions(CellContent::Tree), positions(CellContent::Tent), orthogonal)
+}
+
+/// `match_tents_to_trees` for any two kinds of cell, which the rule engine's `Paired` rule pairs up as trees and tents
+pub fn match_pairs(trees: Vec<(usize, usize)>, tents: Vec<(usize, usize)>, adjacent: impl Fn((usize, usize), (usize, usize)) -> bool) -> TentTreeMatching {
+    let adjacent_tents = trees.iter()
+        .map(|&tree| (0..tents.len()).filter(|&tent| adjacent(tree, tents[tent])).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let mut tree_of_tent = vec![None; tents.len()];
+    for tree in 0..trees.len() {
+        augment(tree, &adjacent_tents, &mut vec![false; tents.len()], &mut tree_of_tent);
+    }
+
+    let mut matching = TentTreeMatching::default();
+    let mut matched_trees = vec![false; trees.len()];
+    for (tent, tree) in tree_of_tent.into_iter().enumerate() {
+        match tree {
+            Some(tree) => {
+                matched_trees[tree] = true;
+                matching.pairs.push((trees[tree], tents[tent]));
+            },
+            None => matching.unmatched_tents.push(tents[tent]),
+        }
+    }
+    matching.unmatched_trees = trees.into_iter()
+        .zip(matched_trees)
+        .filter_map(|(tree, matched)| (!matched).then_some(tree))
+        .collect();
+    matching
+}
+
+// Tries to give `tree` a tent, moving already paired trees to other tents where that frees one up
+fn augment(tree: usize, adjacent_tents: &[Vec<usize>], visited: &mut [bool], tree_of_tent: &mut [Option<usize>]) -> bool {
+    for &tent in adjacent_tents[tree].iter() {
+        if visited[tent] { continue }
+        visited[tent] = true;
+        if tree_of_tent[tent].is_none_or(|other| augment(other, adjacent_tents, visited, tree_of_tent)) {
+            tree_of_tent[tent] = Some(tree);
+            return true;
+        }
+    }
+    false
+}
+
+fn validate_no_adjacent_tents(grid: &Grid) -> bool {
+    grid.cells.iter()
+        .filter(|cell| matches!(cell.value, Some(CellContent::Tent)))
+        .all(|cell| {
+            neighbors(cell.x, cell.y, grid)
+                .all(|(ax, ay)| !matches!(grid.content(ax, ay), Some(CellContent::Tent)))
+        })
+}
+
+fn validate_water_in_row(row: usize, count: u8, grid: &Grid) -> bool {
+    count_in_line(grid, |_, y| y == row, CellContent::Water) == count as usize
+}
+
+fn validate_water_in_column(col: usize, count: u8, grid: &Grid) -> bool {
+    count_in_line(grid, |x, _| x == col, CellContent::Water) == count as usize
+}
+
+// Water settles to the bottom of each region, so every cell at or below its highest water must be water too
+fn validate_water_flows_upward(grid: &Grid, regions: &[Region]) -> bool {
+    let is_water = |&(x, y): &(usize, usize)| matches!(grid.content(x, y), Some(CellContent::Water));
+    regions.iter().all(|region| {
+        let Some(level) = region.cells.iter().filter(|cell| is_water(cell)).map(|&(_, y)| y).min() else { return true };
+        region.cells.iter().filter(|&&(_, y)| y >= level).all(is_water)
+    })
+}
+
+fn validate_runs(grid: &Grid, line: impl Iterator<Item = (usize, usize)>, runs: &[u8]) -> bool {
+    let mut found = Vec::new();
+    let mut run = 0;
+    for (x, y) in line {
+        if matches!(grid.content(x, y), Some(CellContent::Filled)) {
+            run += 1;
+        } else if run > 0 {
+            found.push(run);
+            run = 0;
+        }
+    }
+    if run > 0 {
+        found.push(run);
+    }
+    found == runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::dsl::Cell;
+
+    // `.` is unset, `_` empty, `*` a mine, `A` a tent, `T` a tree, `~` water, `#` filled and digits are numbers
+    fn grid(rows: &[&str]) -> Grid {
+        let cells = rows.iter().enumerate()
+            .flat_map(|(y, row)| row.chars().enumerate().map(move |(x, c)| Cell {
+                x,
+                y,
+                value: match c {
+                    '.' => None,
+                    '_' => Some(CellContent::Empty),
+                    '*' => Some(CellContent::Mine),
+                    'A' => Some(CellContent::Tent),
+                    'T' => Some(CellContent::Tree),
+                    '~' => Some(CellContent::Water),
+                    '#' => Some(CellContent::Filled),
+                    digit => Some(CellContent::Number(digit.to_digit(10).unwrap() as u8)),
+                },
+                region_id: None,
+            }))
+            .collect();
+        Grid { width: rows[0].len(), height: rows.len(), cells }
+    }
+
+    fn region(id: usize, cells: &[(usize, usize)]) -> Region {
+        Region { id, cells: cells.to_vec() }
+    }
+
+    fn check(rule: Rule, rows: &[&str]) -> bool {
+        validate_rule(&rule, &grid(rows), &[])
+    }
+
+    #[test]
+    fn unique_in_row() {
+        assert!(check(Rule::UniqueInRow(0), &["123", "111"]));
+        assert!(!check(Rule::UniqueInRow(1), &["123", "111"]));
+    }
+
+    #[test]
+    fn unique_in_column() {
+        assert!(check(Rule::UniqueInColumn(0), &["12", "21"]));
+        assert!(!check(Rule::UniqueInColumn(0), &["12", "12"]));
+    }
+
+    #[test]
+    fn unique_in_region() {
+        let regions = [region(0, &[(0, 0), (1, 0), (0, 1), (1, 1)])];
+        assert!(validate_rule(&Rule::UniqueInRegion(0), &grid(&["12", "34"]), &regions));
+        assert!(!validate_rule(&Rule::UniqueInRegion(0), &grid(&["12", "31"]), &regions));
+    }
+
+    #[test]
+    fn sum_equals() {
+        let cells = vec![(0, 0), (1, 0), (2, 0)];
+        assert!(check(Rule::SumEquals(cells.clone(), 6), &["123"]));
+        assert!(!check(Rule::SumEquals(cells, 6), &["124"]));
+    }
+
+    #[test]
+    fn adjacent_mines() {
+        assert!(check(Rule::AdjacentMines(1, 1, 2), &["*__", "_1_", "__*"]));
+        assert!(!check(Rule::AdjacentMines(1, 1, 2), &["*__", "_1_", "___"]));
+    }
+
+    #[test]
+    fn adjacent_mines_at_the_edge() {
+        assert!(check(Rule::AdjacentMines(0, 0, 1), &["1*", "__"]));
+        assert!(!check(Rule::AdjacentMines(0, 0, 2), &["1*", "__"]));
+    }
+
+    #[test]
+    fn total_mines() {
+        assert!(check(Rule::TotalMines(2), &["*_", "_*"]));
+        assert!(!check(Rule::TotalMines(2), &["*_", "__"]));
+    }
+
+    #[test]
+    fn tents_in_row() {
+        assert!(check(Rule::TentsInRow(0, 1), &["AT", "__"]));
+        assert!(!check(Rule::TentsInRow(1, 1), &["AT", "__"]));
+    }
+
+    #[test]
+    fn tents_in_column() {
+        assert!(check(Rule::TentsInColumn(0, 1), &["A_", "T_"]));
+        assert!(!check(Rule::TentsInColumn(1, 1), &["A_", "T_"]));
+    }
+
+    #[test]
+    fn tent_tree_pairing() {
+        assert!(check(Rule::TentTreePairing, &["AT_", "__T", "__A"]));
+        assert!(!check(Rule::TentTreePairing, &["AT_", "___", "__A"]));
+    }
+
+    #[test]
+    fn tent_tree_pairing_is_orthogonal() {
+        assert!(!check(Rule::TentTreePairing, &["T_", "_A"]));
+        let matching = match_tents_to_trees(&grid(&["T_", "_A"]));
+        assert_eq!(matching.unmatched_trees, vec![(0, 0)]);
+        assert_eq!(matching.unmatched_tents, vec![(1, 1)]);
+    }
+
+    #[test]
+    fn tent_tree_pairing_repairs_early_choices() {
+        // Pairing the first tree with the first tent it sees would leave the second tree without one
+        let matching = match_tents_to_trees(&grid(&["TAT", "A__"]));
+        assert!(matching.is_perfect());
+        assert_eq!(matching.pairs.len(), 2);
+    }
+
+    #[test]
+    fn tent_tree_pairing_reports_unmatched() {
+        let matching = match_tents_to_trees(&grid(&["AT_", "_TA", "_T_"]));
+        assert_eq!(matching.pairs.len(), 2);
+        assert_eq!(matching.unmatched_trees, vec![(1, 2)]);
+        assert!(matching.unmatched_tents.is_empty());
+    }
+
+    #[test]
+    fn no_adjacent_tents() {
+        assert!(check(Rule::NoAdjacentTents, &["A_A", "T_T"]));
+        assert!(!check(Rule::NoAdjacentTents, &["A__", "_A_"]));
+    }
+
+    #[test]
+    fn water_in_row() {
+        assert!(check(Rule::WaterInRow(1, 2), &["__", "~~"]));
+        assert!(!check(Rule::WaterInRow(0, 2), &["__", "~~"]));
+    }
+
+    #[test]
+    fn water_in_column() {
+        assert!(check(Rule::WaterInColumn(0, 1), &["_", "~"]));
+        assert!(!check(Rule::WaterInColumn(0, 2), &["_", "~"]));
+    }
+
+    #[test]
+    fn water_flows_upward() {
+        let regions = [region(0, &[(0, 0), (1, 0), (0, 1), (1, 1)])];
+        let valid = |rows: &[&str]| validate_rule(&Rule::WaterFlowsUpward, &grid(rows), &regions);
+        assert!(valid(&["__", "~~"]));
+        assert!(valid(&["~~", "~~"]));
+        assert!(!valid(&["~~", "__"]));
+        assert!(!valid(&["__", "_~"]));
+    }
+
+    #[test]
+    fn runs() {
+        assert!(check(Rule::RunsInRow(0, vec![2, 1]), &["##_#_", "_____"]));
+        assert!(!check(Rule::RunsInRow(0, vec![1, 2]), &["##_#_", "_____"]));
+        assert!(check(Rule::RunsInRow(1, Vec::new()), &["##_#_", "_.___"]));
+        assert!(check(Rule::RunsInColumn(0, vec![2]), &["#", "#", "_"]));
+    }
+}
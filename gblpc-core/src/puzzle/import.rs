@@ -0,0 +1,370 @@
+use thiserror::Error;
+
+use super::dsl::{CellContent, CellState, Puzzle, PuzzleDefinition, PuzzleType, Rule};
+
+#[derive(Debug, Error, PartialEq)]
+#[error("line {line}: {kind}")]
+pub struct ImportError {
+    pub line: usize,
+    pub kind: ImportErrorKind,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ImportErrorKind {
+    #[error("expected 81 cells, found {0}")]
+    SudokuLength(usize),
+    #[error("unexpected `{0}`")]
+    UnexpectedChar(char),
+    #[error("bad number `{0}`")]
+    BadNumber(String),
+    #[error("`{0}` is missing")]
+    Missing(&'static str),
+    #[error("expected {expected} clue lines, found {found}")]
+    ClueCount { expected: usize, found: usize },
+    #[error("expected {expected} rows, found {found}")]
+    RowCount { expected: usize, found: usize },
+    #[error("expected {expected} cells, found {found}")]
+    RowLength { expected: usize, found: usize },
+    #[error("{0} has {1} mines around it")]
+    WrongClue(u8, usize),
+}
+
+fn error(line: usize, kind: ImportErrorKind) -> ImportError {
+    ImportError { line, kind }
+}
+
+/// Every puzzle in a collection of 81-character sudoku lines, skipping blank lines and `#` comments
+///
+/// Anything after the first whitespace on a line, such as a rating, is ignored.
+pub fn sudoku_collection(text: &str) -> Result<Vec<PuzzleDefinition>, ImportError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| PuzzleDefinition::from_sudoku_line(line)
+            .map_err(|ImportError { kind, .. }| error(index + 1, kind)))
+        .collect()
+}
+
+impl PuzzleDefinition {
+    /// Reads a 9x9 sudoku row by row, with `1` to `9` for givens and `0` or `.` for blanks
+    pub fn from_sudoku_line(line: &str) -> Result<Self, ImportError> {
+        let cells = line.split_whitespace().next().unwrap_or_default();
+        let length = cells.chars().count();
+        if length != 81 {
+            return Err(error(1, ImportErrorKind::SudokuLength(length)));
+        }
+        let mut puzzle = Puzzle::sudoku(9);
+        let mut initial_state = Vec::with_capacity(81);
+        for (index, c) in cells.chars().enumerate() {
+            let (x, y) = (index % 9, index / 9);
+            let given = match c {
+                '1'..='9' => Some(CellContent::Number(c.to_digit(10).unwrap() as u8)),
+                '0' | '.' => None,
+                _ => return Err(error(1, ImportErrorKind::UnexpectedChar(c))),
+            };
+            if let Some(content) = given.clone() {
+                puzzle.set(x, y, content);
+            }
+            initial_state.push(CellState {
+                x,
+                y,
+                revealed: given.is_some(),
+                content: given.unwrap_or(CellContent::Empty),
+                flagged: false,
+            });
+        }
+        Ok(Self { puzzle, initial_state })
+    }
+
+    /// The givens as an 81-character line, or `None` for anything but a 9x9 sudoku
+    pub fn to_sudoku_line(&self) -> Option<String> {
+        let grid = &self.puzzle.grid;
+        if self.puzzle.puzzle_type != PuzzleType::Sudoku || grid.width != 9 || grid.height != 9 {
+            return None;
+        }
+        Some((0..81)
+            .map(|index| match self.given(index % 9, index / 9) {
+                Some(&CellContent::Number(number @ 1..=9)) => char::from_digit(number as u32, 10).unwrap(),
+                _ => '.',
+            })
+            .collect())
+    }
+
+    /// Reads Steve Simpson's `.non` format: `width`, `height`, then `rows` and `columns` each followed by one line of
+    /// comma-separated runs per line, with `0` for none, and optionally the solution as a `goal` of `0`s and `1`s
+    ///
+    /// Other keywords such as `title` or `by` are skipped.
+    pub fn from_non(text: &str) -> Result<Self, ImportError> {
+        let mut lines = text.lines().enumerate().map(|(index, line)| (index + 1, line.trim()));
+        let (mut width, mut height) = (None, None);
+        let (mut rows, mut columns) = (None, None);
+        let mut goal = None;
+        while let Some((line, content)) = lines.next() {
+            let (keyword, value) = content.split_once(char::is_whitespace).unwrap_or((content, ""));
+            let value = value.trim();
+            let number = || value.parse::<usize>().map_err(|_| error(line, ImportErrorKind::BadNumber(value.to_string())));
+            match keyword {
+                "width" => width = Some(number()?),
+                "height" => height = Some(number()?),
+                "rows" | "columns" => {
+                    let expected = if keyword == "rows" { height } else { width };
+                    let expected = expected.ok_or_else(|| error(line, ImportErrorKind::Missing(if keyword == "rows" { "height" } else { "width" })))?;
+                    let mut clues = Vec::with_capacity(expected);
+                    let mut last = line;
+                    while clues.len() < expected {
+                        let Some((line, content)) = lines.next() else {
+                            return Err(error(last, ImportErrorKind::ClueCount { expected, found: clues.len() }));
+                        };
+                        last = line;
+                        if content.is_empty() { continue }
+                        clues.push(runs(line, content)?);
+                    }
+                    if keyword == "rows" { rows = Some(clues) } else { columns = Some(clues) }
+                },
+                "goal" => goal = Some((line, value.trim_matches('"').to_string())),
+                _ => (),
+            }
+        }
+        let last = text.lines().count().max(1);
+        let width = width.ok_or(error(last, ImportErrorKind::Missing("width")))?;
+        let height = height.ok_or(error(last, ImportErrorKind::Missing("height")))?;
+        let rows = rows.ok_or(error(last, ImportErrorKind::Missing("rows")))?;
+        let columns = columns.ok_or(error(last, ImportErrorKind::Missing("columns")))?;
+
+        let mut puzzle = Puzzle::new(PuzzleType::Nonogram, width, height);
+        puzzle.rules.extend(columns.into_iter().enumerate().map(|(x, runs)| Rule::RunsInColumn(x, runs)));
+        puzzle.rules.extend(rows.into_iter().enumerate().map(|(y, runs)| Rule::RunsInRow(y, runs)));
+        if let Some((line, goal)) = goal {
+            if goal.chars().count() != width * height {
+                return Err(error(line, ImportErrorKind::RowLength { expected: width * height, found: goal.chars().count() }));
+            }
+            for (index, c) in goal.chars().enumerate() {
+                let content = match c {
+                    '1' => CellContent::Filled,
+                    '0' => CellContent::Empty,
+                    _ => return Err(error(line, ImportErrorKind::UnexpectedChar(c))),
+                };
+                puzzle.set(index % width, index / width, content);
+            }
+        }
+        let initial_state = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| CellState {
+                x,
+                y,
+                content: puzzle.grid.content(x, y).cloned().unwrap_or(CellContent::Empty),
+                revealed: false,
+                flagged: false,
+            })
+            .collect();
+        Ok(Self { puzzle, initial_state })
+    }
+
+    /// The clues in `.non` format, with the `goal` when the grid holds a solution, or `None` for anything but a nonogram
+    pub fn to_non(&self) -> Option<String> {
+        if self.puzzle.puzzle_type != PuzzleType::Nonogram { return None }
+        let grid = &self.puzzle.grid;
+        let mut columns = vec![Vec::new(); grid.width];
+        let mut rows = vec![Vec::new(); grid.height];
+        for rule in self.puzzle.rules.iter() {
+            match rule {
+                Rule::RunsInColumn(x, runs) if *x < grid.width => columns[*x].clone_from(runs),
+                Rule::RunsInRow(y, runs) if *y < grid.height => rows[*y].clone_from(runs),
+                _ => (),
+            }
+        }
+        let clue_line = |runs: &Vec<u8>| if runs.is_empty() {
+            "0".to_string()
+        } else {
+            runs.iter().map(u8::to_string).collect::<Vec<_>>().join(",")
+        };
+        let mut text = format!("width {}\nheight {}\n\nrows\n", grid.width, grid.height);
+        for runs in rows.iter() {
+            text.push_str(&clue_line(runs));
+            text.push('\n');
+        }
+        text.push_str("\ncolumns\n");
+        for runs in columns.iter() {
+            text.push_str(&clue_line(runs));
+            text.push('\n');
+        }
+        let cells = (0..grid.height).flat_map(|y| (0..grid.width).map(move |x| (x, y)));
+        if grid.cells.iter().any(|cell| cell.value == Some(CellContent::Filled)) {
+            let goal = cells
+                .map(|(x, y)| if grid.content(x, y) == Some(&CellContent::Filled) { '1' } else { '0' })
+                .collect::<String>();
+            text.push_str(&format!("\ngoal \"{goal}\"\n"));
+        }
+        Some(text)
+    }
+
+    /// Reads a Minesweeper board drawn row by row with `*` for mines, `.` for hidden safe cells and digits for opened
+    /// ones, optionally under a `rows columns` header
+    pub fn from_mine_layout(text: &str) -> Result<Self, ImportError> {
+        let mut lines = text.lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .peekable();
+        let mut size = None;
+        if let Some(&(line, header)) = lines.peek() {
+            let numbers = header.split_whitespace().map(str::parse::<usize>).collect::<Vec<_>>();
+            if let [Ok(rows), Ok(columns)] = numbers[..] {
+                size = Some((line, rows, columns));
+                lines.next();
+            }
+        }
+        let rows = lines.collect::<Vec<_>>();
+        let (first_line, first_row) = *rows.first().ok_or(error(1, ImportErrorKind::Missing("board")))?;
+        let width = first_row.chars().count();
+        let height = rows.len();
+        if let Some((line, rows, columns)) = size {
+            if rows != height {
+                return Err(error(line, ImportErrorKind::RowCount { expected: rows, found: height }));
+            }
+            if columns != width {
+                return Err(error(first_line, ImportErrorKind::RowLength { expected: columns, found: width }));
+            }
+        }
+
+        let mut mines = vec![vec![false; width]; height];
+        let mut opened = vec![vec![None; width]; height];
+        for (y, &(line, row)) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(error(line, ImportErrorKind::RowLength { expected: width, found: row.chars().count() }));
+            }
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '*' => mines[y][x] = true,
+                    '.' => (),
+                    '0'..='8' => opened[y][x] = Some(c.to_digit(10).unwrap() as u8),
+                    _ => return Err(error(line, ImportErrorKind::UnexpectedChar(c))),
+                }
+            }
+        }
+
+        let mut puzzle = Puzzle::new(PuzzleType::Minesweeper, width, height);
+        let mut initial_state = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let content = if mines[y][x] {
+                    CellContent::Mine
+                } else {
+                    let around = (y.saturating_sub(1)..=usize::min(y + 1, height - 1))
+                        .flat_map(|ny| (x.saturating_sub(1)..=usize::min(x + 1, width - 1)).map(move |nx| (nx, ny)))
+                        .filter(|&(nx, ny)| mines[ny][nx])
+                        .count();
+                    if let Some(clue) = opened[y][x].filter(|&clue| clue as usize != around) {
+                        return Err(error(rows[y].0, ImportErrorKind::WrongClue(clue, around)));
+                    }
+                    puzzle.rules.push(Rule::AdjacentMines(x, y, around as u8));
+                    CellContent::Number(around as u8)
+                };
+                puzzle.set(x, y, content.clone());
+                initial_state.push(CellState { x, y, content, revealed: opened[y][x].is_some(), flagged: false });
+            }
+        }
+        let mine_count = mines.iter().flatten().filter(|&&mine| mine).count();
+        puzzle.rules.push(Rule::TotalMines(mine_count as u32));
+        Ok(Self { puzzle, initial_state })
+    }
+
+    /// The board drawn as `from_mine_layout` reads it, or `None` for anything but Minesweeper
+    pub fn to_mine_layout(&self) -> Option<String> {
+        if self.puzzle.puzzle_type != PuzzleType::Minesweeper { return None }
+        let grid = &self.puzzle.grid;
+        let mut text = String::new();
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                text.push(match (grid.content(x, y), self.given(x, y)) {
+                    (Some(CellContent::Mine), _) => '*',
+                    (_, Some(&CellContent::Number(number))) => char::from_digit(number as u32, 10).unwrap_or('.'),
+                    _ => '.',
+                });
+            }
+            text.push('\n');
+        }
+        Some(text)
+    }
+}
+
+// e.g. `3,1,2`, or `0` for a line with nothing filled
+fn runs(line: usize, content: &str) -> Result<Vec<u8>, ImportError> {
+    let runs = content.split(',')
+        .map(|run| run.trim().parse::<u8>().map_err(|_| error(line, ImportErrorKind::BadNumber(run.trim().to_string()))))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(runs.into_iter().filter(|&run| run > 0).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUDOKU: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    #[test]
+    fn sudoku_line() {
+        let definition = PuzzleDefinition::from_sudoku_line(SUDOKU).unwrap();
+        assert_eq!(definition.given(1, 0), Some(&CellContent::Number(3)));
+        assert_eq!(definition.given(2, 0), None);
+        assert_eq!(definition.initial_state.len(), 81);
+        assert_eq!(definition.to_sudoku_line().unwrap(), SUDOKU);
+        assert!(definition.rate().unwrap().solved);
+    }
+
+    #[test]
+    fn sudoku_collection_errors_name_the_line() {
+        let text = format!("# easy ones\n{SUDOKU} 1.2\n\n{}", &SUDOKU[..80]);
+        assert_eq!(sudoku_collection(&text).unwrap_err(), error(4, ImportErrorKind::SudokuLength(80)));
+        let text = format!("{SUDOKU}\n{}x", &SUDOKU[..80]);
+        assert_eq!(sudoku_collection(&text).unwrap_err(), error(2, ImportErrorKind::UnexpectedChar('x')));
+        assert_eq!(sudoku_collection(&format!("{SUDOKU}\n{SUDOKU}")).unwrap().len(), 2);
+    }
+
+    const NON: &str = "title \"Cross\"\nwidth 3\nheight 3\n\nrows\n1\n3\n1\n\ncolumns\n1\n3\n1\n\ngoal \"010111010\"\n";
+
+    #[test]
+    fn non() {
+        let definition = PuzzleDefinition::from_non(NON).unwrap();
+        assert!(matches!(&definition.puzzle.rules[..4], [Rule::RunsInColumn(0, a), Rule::RunsInColumn(1, b), ..] if a == &[1] && b == &[3]));
+        assert_eq!(definition.puzzle.grid.content(1, 1), Some(&CellContent::Filled));
+        assert_eq!(definition.puzzle.grid.content(0, 0), Some(&CellContent::Empty));
+        assert!(definition.initial_state.iter().all(|state| !state.revealed));
+        let exported = definition.to_non().unwrap();
+        assert_eq!(exported, "width 3\nheight 3\n\nrows\n1\n3\n1\n\ncolumns\n1\n3\n1\n\ngoal \"010111010\"\n");
+        assert_eq!(PuzzleDefinition::from_non(&exported).unwrap().to_non().unwrap(), exported);
+    }
+
+    #[test]
+    fn non_errors() {
+        assert_eq!(PuzzleDefinition::from_non("width 3\nheight x").unwrap_err(), error(2, ImportErrorKind::BadNumber("x".to_string())));
+        assert_eq!(PuzzleDefinition::from_non("rows\n1").unwrap_err(), error(1, ImportErrorKind::Missing("height")));
+        assert_eq!(
+            PuzzleDefinition::from_non("width 2\nheight 2\nrows\n1\n1,a").unwrap_err(),
+            error(5, ImportErrorKind::BadNumber("a".to_string())),
+        );
+        assert_eq!(
+            PuzzleDefinition::from_non("width 2\nheight 3\nrows\n1\n1").unwrap_err(),
+            error(5, ImportErrorKind::ClueCount { expected: 3, found: 2 }),
+        );
+    }
+
+    #[test]
+    fn mine_layout() {
+        let definition = PuzzleDefinition::from_mine_layout("2 4\n*1..\n11..\n").unwrap();
+        assert_eq!(definition.puzzle.grid.content(0, 0), Some(&CellContent::Mine));
+        assert_eq!(definition.puzzle.grid.content(3, 1), Some(&CellContent::Number(0)));
+        assert_eq!(definition.given(1, 0), Some(&CellContent::Number(1)));
+        assert_eq!(definition.given(2, 0), None);
+        assert!(definition.puzzle.rules.iter().any(|rule| matches!(rule, Rule::TotalMines(1))));
+        assert_eq!(definition.to_mine_layout().unwrap(), "*1..\n11..\n");
+    }
+
+    #[test]
+    fn mine_layout_errors() {
+        assert_eq!(PuzzleDefinition::from_mine_layout("*1.\n12.").unwrap_err(), error(2, ImportErrorKind::WrongClue(2, 1)));
+        assert_eq!(PuzzleDefinition::from_mine_layout("*1.\n1.").unwrap_err(), error(2, ImportErrorKind::RowLength { expected: 3, found: 2 }));
+        assert_eq!(PuzzleDefinition::from_mine_layout("\n*?").unwrap_err(), error(2, ImportErrorKind::UnexpectedChar('?')));
+        assert_eq!(PuzzleDefinition::from_mine_layout("3 2\n*1\n11").unwrap_err(), error(1, ImportErrorKind::RowCount { expected: 3, found: 2 }));
+    }
+}
@@ -0,0 +1,12 @@
+pub mod dsl;
+pub mod dsl_valiator;
+pub mod cell;
+pub mod direction;
+pub mod grid;
+pub mod position_rule;
+pub mod definition;
+pub mod solver;
+pub mod techniques;
+pub mod difficulty;
+pub mod url;
+pub mod import;
@@ -1,4 +1,4 @@
-// use super::*;
+use super::direction::DirectionSet;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Distance {
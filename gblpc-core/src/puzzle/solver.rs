@@ -0,0 +1,205 @@
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+use super::cell::Cell;
+use super::definition::PuzzleDefinition;
+use super::grid::Grid;
+
+#[derive(Debug, Clone)]
+pub enum Solution<C: Cell> {
+    NoSolution,
+    Unique(Grid<C>),
+    /// Two solutions that differ somewhere, for showing why the puzzle is ambiguous
+    Multiple(Grid<C>, Grid<C>),
+}
+
+// The hidden cells still open after propagation, with the states each could take
+type OpenCells<C> = Vec<(usize, usize, Vec<<C as Cell>::CellState>)>;
+
+impl<C: Cell> PuzzleDefinition<C> {
+    /// Solves for the hidden cells of `grid`, taking its revealed cells as givens
+    pub fn solve(&self, grid: &Grid<C>) -> Solution<C> {
+        let mut solutions = Vec::new();
+        self.search(&mut grid.clone(), 2, &mut None, &mut solutions);
+        let mut solutions = solutions.into_iter();
+        match (solutions.next(), solutions.next()) {
+            (None, _) => Solution::NoSolution,
+            (Some(solution), None) => Solution::Unique(solution),
+            (Some(a), Some(b)) => Solution::Multiple(a, b),
+        }
+    }
+
+    /// Counts the solutions of `grid`, giving up once `limit` have been found
+    pub fn count_solutions(&self, grid: &Grid<C>, limit: usize) -> usize {
+        let mut solutions = Vec::new();
+        self.search(&mut grid.clone(), limit, &mut None, &mut solutions);
+        solutions.len()
+    }
+
+    /// Propagates, then branches on the open cell with the fewest candidates, trying them in a shuffled order when given a rng
+    pub(super) fn search(&self, grid: &mut Grid<C>, limit: usize, rng: &mut Option<&mut ChaCha8Rng>, solutions: &mut Vec<Grid<C>>) {
+        if solutions.len() >= limit { return }
+        let Some(open) = self.propagate(grid) else { return };
+        let Some((row, col, mut states)) = open.into_iter().min_by_key(|(_, _, states)| states.len()) else {
+            solutions.push(grid.clone());
+            return;
+        };
+        if let Some(rng) = rng {
+            states.shuffle(rng);
+        }
+        for state in states {
+            let mut branch = grid.clone();
+            decide(&mut branch, row, col, state);
+            self.search(&mut branch, limit, rng, solutions);
+            if solutions.len() >= limit { return }
+        }
+    }
+
+    /// Decides every hidden cell left with a single candidate until nothing changes, or finds a contradiction
+    fn propagate(&self, grid: &mut Grid<C>) -> Option<OpenCells<C>> {
+        if !self.validate(grid) { return None }
+        loop {
+            let mut open = Vec::new();
+            let mut changed = false;
+            for row in 0..grid.rows {
+                for col in 0..grid.cols {
+                    if grid.get_cell(row, col).is_none_or(Cell::is_revealed) { continue }
+                    let mut states = self.consistent_states(grid, row, col);
+                    match states.len() {
+                        0 => return None,
+                        1 => {
+                            decide(grid, row, col, states.remove(0));
+                            changed = true;
+                        },
+                        _ => open.push((row, col, states)),
+                    }
+                }
+            }
+            if !changed { return Some(open) }
+        }
+    }
+
+    fn consistent_states(&self, grid: &mut Grid<C>, row: usize, col: usize) -> Vec<C::CellState> {
+        let Some(cell) = grid.get_cell(row, col) else { return Vec::new() };
+        let original = cell.get_state().clone();
+        let states = cell.candidate_states().into_iter()
+            .filter(|state| {
+                decide(grid, row, col, state.clone());
+                self.validate(grid)
+            })
+            .collect();
+        if let Some(cell) = grid.get_cell_mut(row, col) {
+            cell.set_state(original);
+            cell.hide();
+        }
+        states
+    }
+}
+
+fn decide<C: Cell>(grid: &mut Grid<C>, row: usize, col: usize, state: C::CellState) {
+    if let Some(cell) = grid.get_cell_mut(row, col) {
+        cell.set_state(state);
+        cell.reveal();
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use super::super::definition::PuzzleRule;
+    use std::collections::HashSet;
+
+    #[derive(Clone, Debug)]
+    pub(in crate::puzzle) struct LatinCell {
+        pos: (usize, usize),
+        value: u8,
+        revealed: bool,
+    }
+    impl Cell for LatinCell {
+        type CellVariant = ();
+        type CellState = u8;
+        fn new(row: usize, col: usize) -> Self {
+            Self { pos: (row, col), value: 0, revealed: false }
+        }
+        fn get_pos(&self) -> (usize, usize) { self.pos }
+        fn get_variant(&self) -> &() { &() }
+        fn get_state(&self) -> &u8 { &self.value }
+        fn set_state(&mut self, state: u8) { self.value = state }
+        fn candidate_states(&self) -> Vec<u8> { (1..=3).collect() }
+        fn is_revealed(&self) -> bool { self.revealed }
+        fn reveal(&mut self) { self.revealed = true }
+        fn hide(&mut self) { self.revealed = false }
+    }
+
+    pub(in crate::puzzle) fn latin_square(size: usize) -> PuzzleDefinition<LatinCell> {
+        let unique = |line: Vec<(usize, usize)>| move |grid: &Grid<LatinCell>| {
+            let mut seen = HashSet::new();
+            line.iter()
+                .filter_map(|&(row, col)| grid.get_cell(row, col))
+                .filter(|cell| cell.is_revealed())
+                .all(|cell| seen.insert(cell.value))
+        };
+        let mut rules = Vec::new();
+        for i in 0..size {
+            rules.push(PuzzleRule {
+                description: format!("row {i}"),
+                condition: Box::new(unique((0..size).map(|col| (i, col)).collect())),
+            });
+            rules.push(PuzzleRule {
+                description: format!("column {i}"),
+                condition: Box::new(unique((0..size).map(|row| (row, i)).collect())),
+            });
+        }
+        PuzzleDefinition { rows: size, cols: size, rules }
+    }
+
+    // Digits are givens and `.` is hidden
+    fn grid(rows: &[&str]) -> Grid<LatinCell> {
+        let cells = rows.iter().enumerate()
+            .map(|(row, line)| line.chars().enumerate().map(|(col, c)| {
+                let mut cell = LatinCell::new(row, col);
+                if let Some(digit) = c.to_digit(10) {
+                    cell.set_state(digit as u8);
+                    cell.reveal();
+                }
+                cell
+            }).collect())
+            .collect();
+        Grid::new(rows.len(), rows[0].len(), cells)
+    }
+
+    fn values(grid: &Grid<LatinCell>) -> Vec<Vec<u8>> {
+        grid.cells.iter().map(|row| row.iter().map(|cell| cell.value).collect()).collect()
+    }
+
+    #[test]
+    fn unique_solution() {
+        let Solution::Unique(solution) = latin_square(3).solve(&grid(&["12.", "2..", "..."])) else { panic!("expected a unique solution") };
+        assert_eq!(values(&solution), vec![vec![1, 2, 3], vec![2, 3, 1], vec![3, 1, 2]]);
+    }
+
+    #[test]
+    fn multiple_solutions_differ() {
+        let Solution::Multiple(a, b) = latin_square(3).solve(&grid(&["...", "...", "..."])) else { panic!("expected multiple solutions") };
+        assert_ne!(values(&a), values(&b));
+    }
+
+    #[test]
+    fn no_solution() {
+        // Row 0 forces a 2 into column 1, which already has one
+        let mut definition = latin_square(2);
+        definition.rules.push(PuzzleRule {
+            description: "only 1 and 2".to_string(),
+            condition: Box::new(|grid| grid.cells.iter().flatten().all(|cell| !cell.is_revealed() || cell.value <= 2)),
+        });
+        assert!(matches!(definition.solve(&grid(&["1.", ".2"])), Solution::NoSolution));
+    }
+
+    #[test]
+    fn count_solutions_stops_at_limit() {
+        let definition = latin_square(3);
+        let empty = grid(&["...", "...", "..."]);
+        assert_eq!(definition.count_solutions(&empty, 100), 12);
+        assert_eq!(definition.count_solutions(&empty, 5), 5);
+    }
+}
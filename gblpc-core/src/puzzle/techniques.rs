@@ -0,0 +1,618 @@
+use crate::game::Neighborhood;
+
+/// A deduction a person could make, roughly from the simplest to the most involved within each game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Technique {
+    /// A cell with one candidate left
+    NakedSingle,
+    /// A digit with one place left in a unit
+    HiddenSingle,
+    /// Two cells of a unit sharing the same two candidates
+    NakedPair,
+    /// Two digits confined to the same two cells of a unit
+    HiddenPair,
+    /// A digit confined to the same two columns in two rows, or the other way round
+    XWing,
+    /// A number whose hidden neighbours are all mines or all safe
+    SingleClue,
+    /// A number whose hidden neighbours are among another's
+    ClueSubset,
+    /// The mines left against the hidden cells left
+    MineCount,
+    /// A cell with no tree beside it, which can only be grass
+    NoTreeNearby,
+    /// A row or column whose tents are all placed, or whose open cells are all needed for them
+    TentCount,
+    /// A tree without a tent that has a single open cell beside it
+    LoneTree,
+    /// Cells a line's runs cover, or miss, however they sit in an empty line
+    LineOverlap,
+    /// Cells a line's runs cover, or miss, in every arrangement that fits the cells already known
+    LineLogic,
+}
+
+/// Solves a Latin-square style puzzle, Sudoku included, the way a person would
+pub struct SudokuLogic {
+    size: usize,
+    units: Vec<Vec<(usize, usize)>>,
+    values: Vec<Vec<Option<u8>>>,
+    candidates: Vec<Vec<Vec<u8>>>,
+}
+impl SudokuLogic {
+    /// `values[y][x]` are the givens, and every unit may hold each of `1..=size` only once
+    pub fn new(values: Vec<Vec<Option<u8>>>, units: Vec<Vec<(usize, usize)>>) -> Self {
+        let size = values.len();
+        let mut logic = Self {
+            size,
+            units,
+            values: vec![vec![None; size]; size],
+            candidates: vec![vec![(1..=size as u8).collect(); size]; size],
+        };
+        for (y, row) in values.into_iter().enumerate() {
+            for (x, value) in row.into_iter().enumerate() {
+                if let Some(value) = value {
+                    logic.place((x, y), value);
+                }
+            }
+        }
+        logic
+    }
+
+    pub fn values(&self) -> &[Vec<Option<u8>>] {
+        &self.values
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.values.iter().flatten().all(Option::is_some)
+    }
+
+    /// Applies the simplest technique that gets anywhere
+    pub fn step(&mut self) -> Option<Technique> {
+        if self.naked_single() { return Some(Technique::NakedSingle) }
+        if self.hidden_single() { return Some(Technique::HiddenSingle) }
+        if self.naked_pair() { return Some(Technique::NakedPair) }
+        if self.hidden_pair() { return Some(Technique::HiddenPair) }
+        if self.x_wing() { return Some(Technique::XWing) }
+        None
+    }
+
+    fn place(&mut self, (x, y): (usize, usize), value: u8) {
+        self.values[y][x] = Some(value);
+        self.candidates[y][x].clear();
+        for unit in self.units.iter().filter(|unit| unit.contains(&(x, y))) {
+            for &(ux, uy) in unit.iter() {
+                self.candidates[uy][ux].retain(|&candidate| candidate != value);
+            }
+        }
+    }
+
+    // Drops the candidates of a cell that fail `keep`, telling whether any did
+    fn eliminate(&mut self, (x, y): (usize, usize), keep: impl Fn(u8) -> bool) -> bool {
+        let candidates = &mut self.candidates[y][x];
+        let before = candidates.len();
+        candidates.retain(|&candidate| keep(candidate));
+        candidates.len() != before
+    }
+
+    fn places(&self, unit: &[(usize, usize)], value: u8) -> Vec<(usize, usize)> {
+        unit.iter().copied().filter(|&(x, y)| self.candidates[y][x].contains(&value)).collect()
+    }
+
+    fn naked_single(&mut self) -> bool {
+        let size = self.size;
+        let single = (0..size)
+            .flat_map(|y| (0..size).map(move |x| (x, y)))
+            .find_map(|(x, y)| match self.candidates[y][x][..] {
+                [value] => Some(((x, y), value)),
+                _ => None,
+            });
+        let Some((pos, value)) = single else { return false };
+        self.place(pos, value);
+        true
+    }
+
+    fn hidden_single(&mut self) -> bool {
+        for unit in self.units.clone() {
+            for value in 1..=self.size as u8 {
+                if let [pos] = self.places(&unit, value)[..] {
+                    self.place(pos, value);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn naked_pair(&mut self) -> bool {
+        for unit in self.units.clone() {
+            for (i, &a) in unit.iter().enumerate() {
+                let pair = self.candidates[a.1][a.0].clone();
+                if pair.len() != 2 { continue }
+                let Some(&b) = unit[i + 1..].iter().find(|&&(x, y)| self.candidates[y][x] == pair) else { continue };
+                let mut progress = false;
+                for &pos in unit.iter().filter(|&&pos| pos != a && pos != b) {
+                    progress |= self.eliminate(pos, |candidate| !pair.contains(&candidate));
+                }
+                if progress { return true }
+            }
+        }
+        false
+    }
+
+    fn hidden_pair(&mut self) -> bool {
+        for unit in self.units.clone() {
+            for a in 1..=self.size as u8 {
+                let places = self.places(&unit, a);
+                if places.len() != 2 { continue }
+                for b in a + 1..=self.size as u8 {
+                    if self.places(&unit, b) != places { continue }
+                    let mut progress = false;
+                    for &pos in places.iter() {
+                        progress |= self.eliminate(pos, |candidate| candidate == a || candidate == b);
+                    }
+                    if progress { return true }
+                }
+            }
+        }
+        false
+    }
+
+    fn x_wing(&mut self) -> bool {
+        let size = self.size;
+        for value in 1..=size as u8 {
+            for transposed in [false, true] {
+                // Lines are rows, or columns once transposed, and `i` runs along them
+                let at = |line: usize, i: usize| if transposed { (line, i) } else { (i, line) };
+                let spots = (0..size)
+                    .map(|line| (0..size).filter(|&i| {
+                        let (x, y) = at(line, i);
+                        self.candidates[y][x].contains(&value)
+                    }).collect::<Vec<_>>())
+                    .collect::<Vec<_>>();
+                for a in 0..size {
+                    if spots[a].len() != 2 { continue }
+                    for b in a + 1..size {
+                        if spots[b] != spots[a] { continue }
+                        let mut progress = false;
+                        for line in (0..size).filter(|&line| line != a && line != b) {
+                            for &i in spots[a].iter() {
+                                progress |= self.eliminate(at(line, i), |candidate| candidate != value);
+                            }
+                        }
+                        if progress { return true }
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+/// Plays Minesweeper without guessing, opening only cells it has proven safe
+pub struct MineLogic<'a> {
+    mines: &'a [Vec<bool>],
+    width: usize,
+    height: usize,
+    opened: Vec<Vec<bool>>,
+    flagged: Vec<Vec<bool>>,
+}
+impl<'a> MineLogic<'a> {
+    /// `mines[y][x]` is the hidden layout, which is only looked at for the cells it opens
+    pub fn new(mines: &'a [Vec<bool>]) -> Self {
+        let (height, width) = (mines.len(), mines.first().map_or(0, Vec::len));
+        Self {
+            mines,
+            width,
+            height,
+            opened: vec![vec![false; width]; height],
+            flagged: vec![vec![false; width]; height],
+        }
+    }
+
+    pub fn is_mine(&self, (x, y): (usize, usize)) -> bool {
+        self.mines[y][x]
+    }
+
+    fn is_unknown(&self, (x, y): (usize, usize)) -> bool {
+        !self.opened[y][x] && !self.flagged[y][x]
+    }
+
+    fn neighbors(&self, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        let mut neighbors = Vec::new();
+        for ny in y.saturating_sub(1)..=usize::min(y + 1, self.height - 1) {
+            for nx in x.saturating_sub(1)..=usize::min(x + 1, self.width - 1) {
+                if (nx, ny) != (x, y) {
+                    neighbors.push((nx, ny));
+                }
+            }
+        }
+        neighbors
+    }
+
+    fn number(&self, pos: (usize, usize)) -> usize {
+        self.neighbors(pos).into_iter().filter(|&pos| self.is_mine(pos)).count()
+    }
+
+    /// Opens a cell known to be safe, spreading over zeros like a click would
+    pub fn open(&mut self, pos: (usize, usize)) {
+        let mut stack = vec![pos];
+        while let Some(pos @ (x, y)) = stack.pop() {
+            if !self.is_unknown(pos) { continue }
+            self.opened[y][x] = true;
+            if self.number(pos) == 0 {
+                stack.extend(self.neighbors(pos));
+            }
+        }
+    }
+
+    // Every opened number with hidden neighbours, as the hidden cells and how many mines are still among them
+    fn constraints(&self) -> Vec<(Vec<(usize, usize)>, usize)> {
+        let mut constraints = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !self.opened[y][x] { continue }
+                let around = self.neighbors((x, y));
+                let flags = around.iter().filter(|&&(nx, ny)| self.flagged[ny][nx]).count();
+                let unknown = around.into_iter().filter(|&pos| self.is_unknown(pos)).collect::<Vec<_>>();
+                if !unknown.is_empty() {
+                    constraints.push((unknown, self.number((x, y)) - flags));
+                }
+            }
+        }
+        constraints
+    }
+
+    /// Applies the simplest technique that gets anywhere
+    pub fn step(&mut self) -> Option<Technique> {
+        let constraints = self.constraints();
+        let mut safe = Vec::new();
+        let mut found = Vec::new();
+        let mut technique = Technique::SingleClue;
+        for (cells, mines) in constraints.iter() {
+            if *mines == 0 { safe.extend(cells.iter().copied()) }
+            if *mines == cells.len() { found.extend(cells.iter().copied()) }
+        }
+        if safe.is_empty() && found.is_empty() {
+            technique = Technique::ClueSubset;
+            for (small, small_mines) in constraints.iter() {
+                for (large, large_mines) in constraints.iter() {
+                    if small.len() >= large.len() || !small.iter().all(|pos| large.contains(pos)) { continue }
+                    let rest = large.iter().filter(|pos| !small.contains(pos)).copied().collect::<Vec<_>>();
+                    let mines = large_mines - small_mines;
+                    if mines == 0 { safe.extend(rest.iter().copied()) }
+                    if mines == rest.len() { found.extend(rest) }
+                }
+            }
+        }
+        if safe.is_empty() && found.is_empty() {
+            technique = Technique::MineCount;
+            let unknown = (0..self.height)
+                .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+                .filter(|&pos| self.is_unknown(pos))
+                .collect::<Vec<_>>();
+            let total = self.mines.iter().flatten().filter(|&&mine| mine).count();
+            let flags = self.flagged.iter().flatten().filter(|&&flag| flag).count();
+            if total == flags { safe.extend(unknown.iter().copied()) }
+            if total - flags == unknown.len() { found.extend(unknown) }
+        }
+
+        let progress = safe.iter().chain(found.iter()).any(|&pos| self.is_unknown(pos));
+        for (x, y) in found {
+            self.flagged[y][x] = true;
+        }
+        for pos in safe {
+            self.open(pos);
+        }
+        progress.then_some(technique)
+    }
+
+    pub fn is_solved(&self) -> bool {
+        (0..self.height).all(|y| (0..self.width).all(|x| self.opened[y][x] || self.mines[y][x]))
+    }
+}
+
+/// Solves Tents and Trees the way a person would, with `Some(true)` for a tent and `Some(false)` for grass
+pub struct TentsLogic {
+    width: usize,
+    height: usize,
+    trees: Vec<Vec<bool>>,
+    rows: Vec<Option<usize>>,
+    columns: Vec<Option<usize>>,
+    tents: Vec<Vec<Option<bool>>>,
+}
+impl TentsLogic {
+    /// `trees[y][x]`, with the tents each row and column must hold where it has a clue
+    pub fn new(trees: Vec<Vec<bool>>, rows: Vec<Option<usize>>, columns: Vec<Option<usize>>) -> Self {
+        let (height, width) = (trees.len(), trees.first().map_or(0, Vec::len));
+        let tents = trees.iter().map(|row| row.iter().map(|&tree| tree.then_some(false)).collect()).collect();
+        Self { width, height, trees, rows, columns, tents }
+    }
+
+    pub fn tents(&self) -> &[Vec<Option<bool>>] {
+        &self.tents
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.tents.iter().flatten().all(Option::is_some)
+    }
+
+    /// Applies the simplest technique that gets anywhere
+    pub fn step(&mut self) -> Option<Technique> {
+        if self.no_tree_nearby() { return Some(Technique::NoTreeNearby) }
+        if self.tent_count() { return Some(Technique::TentCount) }
+        if self.lone_tree() { return Some(Technique::LoneTree) }
+        None
+    }
+
+    fn beside(&self, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        Neighborhood::Orthogonal.neighbors(x, y, self.width, self.height)
+    }
+
+    fn open(&self) -> Vec<(usize, usize)> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.tents[y][x].is_none())
+            .collect()
+    }
+
+    // A tent keeps every other tent out of the cells around it
+    fn pitch(&mut self, (x, y): (usize, usize)) {
+        self.tents[y][x] = Some(true);
+        for (nx, ny) in Neighborhood::Moore.neighbors(x, y, self.width, self.height) {
+            self.tents[ny][nx].get_or_insert(false);
+        }
+    }
+
+    fn no_tree_nearby(&mut self) -> bool {
+        let grass = self.open().into_iter()
+            .filter(|&pos| self.beside(pos).iter().all(|&(x, y)| !self.trees[y][x]))
+            .collect::<Vec<_>>();
+        for &(x, y) in grass.iter() {
+            self.tents[y][x] = Some(false);
+        }
+        !grass.is_empty()
+    }
+
+    fn tent_count(&mut self) -> bool {
+        let lines = self.rows.iter().enumerate()
+            .map(|(y, &clue)| (clue, (0..self.width).map(|x| (x, y)).collect::<Vec<_>>()))
+            .chain(self.columns.iter().enumerate().map(|(x, &clue)| (clue, (0..self.height).map(|y| (x, y)).collect())))
+            .collect::<Vec<_>>();
+        for (clue, line) in lines {
+            let Some(clue) = clue else { continue };
+            let pitched = line.iter().filter(|&&(x, y)| self.tents[y][x] == Some(true)).count();
+            let open = line.into_iter().filter(|&(x, y)| self.tents[y][x].is_none()).collect::<Vec<_>>();
+            if open.is_empty() { continue }
+            if pitched == clue {
+                for &(x, y) in open.iter() {
+                    self.tents[y][x] = Some(false);
+                }
+                return true;
+            }
+            if pitched + open.len() == clue {
+                for pos in open {
+                    self.pitch(pos);
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    fn lone_tree(&mut self) -> bool {
+        for (x, y) in (0..self.height).flat_map(|y| (0..self.width).map(move |x| (x, y))) {
+            if !self.trees[y][x] { continue }
+            let beside = self.beside((x, y));
+            if beside.iter().any(|&(nx, ny)| self.tents[ny][nx] == Some(true)) { continue }
+            if let [pos] = beside.into_iter().filter(|&(nx, ny)| self.tents[ny][nx].is_none()).collect::<Vec<_>>()[..] {
+                self.pitch(pos);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Solves a nonogram one line at a time, with `Some(true)` for a filled cell and `Some(false)` for a blank one
+pub struct NonogramLogic {
+    rows: Vec<Vec<usize>>,
+    columns: Vec<Vec<usize>>,
+    cells: Vec<Vec<Option<bool>>>,
+}
+impl NonogramLogic {
+    /// The runs along each row and each column, in order
+    pub fn new(rows: Vec<Vec<usize>>, columns: Vec<Vec<usize>>) -> Self {
+        let cells = vec![vec![None; columns.len()]; rows.len()];
+        Self { rows, columns, cells }
+    }
+
+    pub fn cells(&self) -> &[Vec<Option<bool>>] {
+        &self.cells
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.cells.iter().flatten().all(Option::is_some)
+    }
+
+    /// Applies the simplest technique that gets anywhere
+    pub fn step(&mut self) -> Option<Technique> {
+        if self.settle_lines(false) { return Some(Technique::LineOverlap) }
+        if self.settle_lines(true) { return Some(Technique::LineLogic) }
+        None
+    }
+
+    // The cells of every column and then every row, with their runs
+    fn lines(&self) -> Vec<(Vec<usize>, Vec<(usize, usize)>)> {
+        let (width, height) = (self.columns.len(), self.rows.len());
+        self.columns.iter().enumerate()
+            .map(|(x, runs)| (runs.clone(), (0..height).map(|y| (x, y)).collect()))
+            .chain(self.rows.iter().enumerate().map(|(y, runs)| (runs.clone(), (0..width).map(|x| (x, y)).collect())))
+            .collect()
+    }
+
+    // Settles what the first line that can get anywhere allows, reading its known cells only when `known` is set
+    fn settle_lines(&mut self, known: bool) -> bool {
+        for (runs, line) in self.lines() {
+            let cells = line.iter()
+                .map(|&(x, y)| if known { self.cells[y][x] } else { None })
+                .collect::<Vec<_>>();
+            let Some(settled) = settle(&runs, &cells) else { continue };
+            let mut progress = false;
+            for (&(x, y), settled) in line.iter().zip(settled) {
+                if self.cells[y][x].is_none() && settled.is_some() {
+                    self.cells[y][x] = settled;
+                    progress = true;
+                }
+            }
+            if progress { return true }
+        }
+        false
+    }
+}
+
+// What every arrangement of `runs` that fits the known cells of `line` agrees on, or `None` when none fits
+fn settle(runs: &[usize], line: &[Option<bool>]) -> Option<Vec<Option<bool>>> {
+    let (length, count) = (line.len(), runs.len());
+    let fits_run = |at: usize, run: usize| at + run <= length
+        && line[at..at + run].iter().all(|&cell| cell != Some(false))
+        && line.get(at + run) != Some(&Some(true));
+    // `rest[at][next]`: whether runs `next..` fit in the cells from `at` on
+    let mut rest = vec![vec![false; count + 1]; length + 2];
+    rest[length][count] = true;
+    rest[length + 1][count] = true;
+    for at in (0..length).rev() {
+        for next in (0..=count).rev() {
+            let blank = line[at] != Some(true) && rest[at + 1][next];
+            let filled = next < count && fits_run(at, runs[next]) && rest[at + runs[next] + 1][next + 1];
+            rest[at][next] = blank || filled;
+        }
+    }
+    if !rest[0][0] { return None }
+    let (mut can_fill, mut can_blank) = (vec![false; length], vec![false; length]);
+    let mut reached = vec![vec![false; count + 1]; length + 2];
+    reached[0][0] = true;
+    for at in 0..length {
+        for next in 0..=count {
+            if !reached[at][next] { continue }
+            if line[at] != Some(true) && rest[at + 1][next] {
+                can_blank[at] = true;
+                reached[at + 1][next] = true;
+            }
+            if next < count && fits_run(at, runs[next]) && rest[at + runs[next] + 1][next + 1] {
+                let end = at + runs[next];
+                can_fill[at..end].iter_mut().for_each(|cell| *cell = true);
+                if end < length {
+                    can_blank[end] = true;
+                }
+                reached[end + 1][next + 1] = true;
+            }
+        }
+    }
+    Some(can_fill.into_iter().zip(can_blank).map(|(fill, blank)| (fill != blank).then_some(fill)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sudoku_units(size: usize, box_width: usize, box_height: usize) -> Vec<Vec<(usize, usize)>> {
+        let mut units = Vec::new();
+        for i in 0..size {
+            units.push((0..size).map(|x| (x, i)).collect());
+            units.push((0..size).map(|y| (i, y)).collect());
+        }
+        for by in (0..size).step_by(box_height) {
+            for bx in (0..size).step_by(box_width) {
+                units.push((by..by + box_height).flat_map(|y| (bx..bx + box_width).map(move |x| (x, y))).collect());
+            }
+        }
+        units
+    }
+
+    // Digits are givens and `.` is empty
+    fn sudoku(rows: &[&str], box_width: usize, box_height: usize) -> SudokuLogic {
+        let values = rows.iter()
+            .map(|row| row.chars().map(|c| c.to_digit(10).map(|digit| digit as u8)).collect())
+            .collect();
+        SudokuLogic::new(values, sudoku_units(rows.len(), box_width, box_height))
+    }
+
+    #[test]
+    fn singles_solve_an_easy_sudoku() {
+        let mut logic = sudoku(&["1...", "..3.", ".4..", "...2"], 2, 2);
+        while logic.step().is_some() {}
+        assert!(logic.is_solved());
+        assert_eq!(logic.values()[0], vec![Some(1), Some(3), Some(2), Some(4)]);
+    }
+
+    #[test]
+    fn naked_pair_clears_the_rest_of_its_unit() {
+        let mut logic = sudoku(&["....", "....", "....", "...."], 2, 2);
+        logic.candidates[0][0] = vec![1, 2];
+        logic.candidates[0][1] = vec![1, 2];
+        // One unit per step, the row and then the box
+        assert!(logic.naked_pair());
+        assert_eq!(logic.candidates[0][2], vec![3, 4]);
+        assert!(logic.naked_pair());
+        assert_eq!(logic.candidates[1][0], vec![3, 4]);
+    }
+
+    #[test]
+    fn x_wing_clears_its_columns() {
+        let mut logic = sudoku(&["....", "....", "....", "...."], 4, 1);
+        for x in [1, 2] {
+            logic.eliminate((x, 0), |candidate| candidate != 1);
+            logic.eliminate((x, 2), |candidate| candidate != 1);
+        }
+        assert!(!logic.naked_pair() && !logic.hidden_pair());
+        assert!(logic.x_wing());
+        assert!(!logic.candidates[1][0].contains(&1) && !logic.candidates[3][3].contains(&1));
+        assert!(logic.candidates[1][1].contains(&1));
+    }
+
+    #[test]
+    fn tents_follow_from_counts_and_lone_trees() {
+        // T.
+        // ..
+        let mut logic = TentsLogic::new(vec![vec![true, false], vec![false, false]], vec![Some(0), Some(1)], vec![Some(1), Some(0)]);
+        assert_eq!(logic.step(), Some(Technique::NoTreeNearby));
+        assert_eq!(logic.step(), Some(Technique::TentCount));
+        assert_eq!(logic.step(), Some(Technique::TentCount));
+        assert!(logic.is_solved());
+        assert_eq!(logic.tents()[1], vec![Some(true), Some(false)]);
+
+        let mut logic = TentsLogic::new(vec![vec![true, false, false]], vec![None], vec![None; 3]);
+        assert_eq!(logic.step(), Some(Technique::NoTreeNearby));
+        assert_eq!(logic.step(), Some(Technique::LoneTree));
+        assert!(logic.is_solved());
+    }
+
+    #[test]
+    fn line_overlap_then_line_logic() {
+        assert_eq!(settle(&[3], &[None; 4]), Some(vec![None, Some(true), Some(true), None]));
+        assert_eq!(settle(&[1, 1], &[None; 3]), Some(vec![Some(true), Some(false), Some(true)]));
+        assert_eq!(settle(&[1, 1], &[None, None, Some(false), None]), Some(vec![None, None, Some(false), Some(true)]));
+        assert_eq!(settle(&[2], &[Some(true), Some(false), None]), None);
+
+        // ##.
+        // #..
+        // ##.
+        let mut logic = NonogramLogic::new(vec![vec![2], vec![1], vec![2]], vec![vec![3], vec![1, 1], vec![]]);
+        assert_eq!(logic.step(), Some(Technique::LineOverlap));
+        while logic.step() == Some(Technique::LineOverlap) {}
+        while logic.step().is_some() {}
+        assert!(logic.is_solved());
+        assert_eq!(logic.cells()[1], vec![Some(true), Some(false), Some(false)]);
+    }
+
+    #[test]
+    fn mine_techniques_are_reported_in_order() {
+        let mines = vec![vec![false, false, false], vec![false, false, false], vec![false, true, false]];
+        let mut logic = MineLogic::new(&mines);
+        logic.open((0, 0));
+        assert_eq!(logic.step(), Some(Technique::ClueSubset));
+        assert_eq!(logic.step(), Some(Technique::SingleClue));
+        assert_eq!(logic.step(), None);
+        assert!(logic.is_solved());
+    }
+}
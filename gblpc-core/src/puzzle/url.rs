@@ -0,0 +1,418 @@
+use thiserror::Error;
+
+use super::dsl::{CellContent, Puzzle, PuzzleDefinition, PuzzleType, Region, Rule};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum UrlError {
+    #[error("no `type/columns/rows/data` puzzle in the URL")]
+    NotAPuzzle,
+    #[error("puzz.link type `{0}` isn't supported")]
+    UnknownType(String),
+    #[error("bad board size `{0}`")]
+    BadSize(String),
+    #[error("unexpected `{found}` at {at} in the puzzle data")]
+    BadData { at: usize, found: char },
+    #[error("the puzzle data ends early")]
+    TooShort,
+    #[error("{0:?} puzzles have no puzz.link format")]
+    Unsupported(PuzzleType),
+}
+
+impl PuzzleType {
+    fn pid(&self) -> Option<&'static str> {
+        match self {
+            Self::Sudoku => Some("sudoku"),
+            Self::Minesweeper => Some("mines"),
+            Self::TentsAndTrees => Some("tents"),
+            Self::Aquarium => Some("aquarium"),
+            Self::Nonogram => Some("nonogram"),
+            Self::Crossword => None,
+        }
+    }
+
+    fn from_pid(pid: &str) -> Option<Self> {
+        [Self::Sudoku, Self::Minesweeper, Self::TentsAndTrees, Self::Aquarium, Self::Nonogram]
+            .into_iter()
+            .find(|puzzle_type| puzzle_type.pid() == Some(pid))
+    }
+}
+
+/// Reads puzz.link and pzv.jp links, or just the `type/columns/rows/data` after their `?`
+///
+/// Clue numbers use pzprjs' hexadecimal encoding, where `g` to `z` skip 1 to 20 cells, and region borders its 5-bit
+/// packing, vertical borders first. Clues outside the grid run along the columns and then the rows. Tents follow their
+/// clues with the trees as a packed bitmap, and nonograms list each line's runs ended by `0`.
+impl PuzzleDefinition {
+    pub fn from_url(url: &str) -> Result<Self, UrlError> {
+        let query = url.split_once('?').map_or(url, |(_, query)| query);
+        let mut parts = query.split('/');
+        let pid = parts.next().filter(|pid| !pid.is_empty()).ok_or(UrlError::NotAPuzzle)?;
+        let puzzle_type = PuzzleType::from_pid(pid).ok_or_else(|| UrlError::UnknownType(pid.to_string()))?;
+        // Some links carry flags such as `v:` before the size
+        let mut parts = parts.skip_while(|part| part.parse::<usize>().is_err());
+        let mut size = || {
+            let part = parts.next().ok_or(UrlError::NotAPuzzle)?;
+            part.parse::<usize>().ok().filter(|&size| size > 0).ok_or_else(|| UrlError::BadSize(part.to_string()))
+        };
+        let (width, height) = (size()?, size()?);
+        let body = parts.collect::<Vec<_>>().join("/");
+        let mut reader = Reader { body: body.as_bytes(), at: 0 };
+        let mut puzzle = Puzzle::new(puzzle_type.clone(), width, height);
+
+        match puzzle_type {
+            PuzzleType::Sudoku => {
+                if width != height {
+                    return Err(UrlError::BadSize(format!("{width}x{height}")));
+                }
+                puzzle = Puzzle::sudoku(width);
+                fill_numbers(&mut puzzle, &reader.numbers(width * height)?);
+            },
+            PuzzleType::Minesweeper => {
+                let numbers = reader.numbers(width * height)?;
+                fill_numbers(&mut puzzle, &numbers);
+                for (index, number) in numbers.into_iter().enumerate() {
+                    if let Some(number) = number {
+                        puzzle.rules.push(Rule::AdjacentMines(index % width, index / width, number));
+                    }
+                }
+            },
+            PuzzleType::TentsAndTrees => {
+                let (columns, rows) = reader.outside_numbers(width, height)?;
+                for (index, tree) in reader.bits(width * height)?.into_iter().enumerate() {
+                    if tree {
+                        puzzle.set(index % width, index / width, CellContent::Tree);
+                    }
+                }
+                puzzle.rules.extend(columns.into_iter().enumerate().filter_map(|(x, count)| Some(Rule::TentsInColumn(x, count?))));
+                puzzle.rules.extend(rows.into_iter().enumerate().filter_map(|(y, count)| Some(Rule::TentsInRow(y, count?))));
+                puzzle.rules.extend([Rule::TentTreePairing, Rule::NoAdjacentTents]);
+            },
+            PuzzleType::Aquarium => {
+                let vertical = reader.bits((width - 1) * height)?;
+                let horizontal = reader.bits(width * (height - 1))?;
+                for region in regions_within(width, height, &vertical, &horizontal) {
+                    puzzle.add_region(region);
+                }
+                let (columns, rows) = reader.outside_numbers(width, height)?;
+                puzzle.rules.extend(columns.into_iter().enumerate().filter_map(|(x, count)| Some(Rule::WaterInColumn(x, count?))));
+                puzzle.rules.extend(rows.into_iter().enumerate().filter_map(|(y, count)| Some(Rule::WaterInRow(y, count?))));
+                puzzle.rules.push(Rule::WaterFlowsUpward);
+            },
+            PuzzleType::Nonogram => {
+                for x in 0..width {
+                    puzzle.rules.push(Rule::RunsInColumn(x, reader.runs()?));
+                }
+                for y in 0..height {
+                    puzzle.rules.push(Rule::RunsInRow(y, reader.runs()?));
+                }
+            },
+            PuzzleType::Crossword => unreachable!("crosswords have no pid"),
+        }
+        Ok(Self::new(puzzle))
+    }
+
+    /// A puzz.link link to the puzzle's givens, clues and regions
+    pub fn to_url(&self) -> Result<String, UrlError> {
+        let puzzle = &self.puzzle;
+        let pid = puzzle.puzzle_type.pid().ok_or_else(|| UrlError::Unsupported(puzzle.puzzle_type.clone()))?;
+        let (width, height) = (puzzle.grid.width, puzzle.grid.height);
+        let cells = || (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+        let mut body = String::new();
+        match puzzle.puzzle_type {
+            PuzzleType::Sudoku | PuzzleType::Minesweeper => {
+                let numbers = cells()
+                    .map(|(x, y)| match self.given(x, y) {
+                        Some(&CellContent::Number(number)) => Some(number),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                write_numbers(&mut body, &numbers);
+            },
+            PuzzleType::TentsAndTrees => {
+                let (columns, rows) = self.outside_numbers(|rule| match *rule {
+                    Rule::TentsInColumn(x, count) => Some((true, x, count)),
+                    Rule::TentsInRow(y, count) => Some((false, y, count)),
+                    _ => None,
+                });
+                write_numbers(&mut body, &[columns, rows].concat());
+                let trees = cells().map(|(x, y)| puzzle.grid.content(x, y) == Some(&CellContent::Tree)).collect::<Vec<_>>();
+                write_bits(&mut body, &trees);
+            },
+            PuzzleType::Aquarium => {
+                let region = |x: usize, y: usize| puzzle.grid.get(x, y).and_then(|cell| cell.region_id);
+                let vertical = (0..height)
+                    .flat_map(|y| (0..width - 1).map(move |x| (x, y)))
+                    .map(|(x, y)| region(x, y) != region(x + 1, y))
+                    .collect::<Vec<_>>();
+                let horizontal = (0..height - 1)
+                    .flat_map(|y| (0..width).map(move |x| (x, y)))
+                    .map(|(x, y)| region(x, y) != region(x, y + 1))
+                    .collect::<Vec<_>>();
+                write_bits(&mut body, &vertical);
+                write_bits(&mut body, &horizontal);
+                let (columns, rows) = self.outside_numbers(|rule| match *rule {
+                    Rule::WaterInColumn(x, count) => Some((true, x, count)),
+                    Rule::WaterInRow(y, count) => Some((false, y, count)),
+                    _ => None,
+                });
+                write_numbers(&mut body, &[columns, rows].concat());
+            },
+            PuzzleType::Nonogram => {
+                let mut columns = vec![Vec::new(); width];
+                let mut rows = vec![Vec::new(); height];
+                for rule in puzzle.rules.iter() {
+                    match rule {
+                        Rule::RunsInColumn(x, runs) if *x < width => columns[*x].clone_from(runs),
+                        Rule::RunsInRow(y, runs) if *y < height => rows[*y].clone_from(runs),
+                        _ => (),
+                    }
+                }
+                for runs in columns.iter().chain(rows.iter()) {
+                    for &run in runs.iter() {
+                        write_number(&mut body, run);
+                    }
+                    body.push('0');
+                }
+            },
+            PuzzleType::Crossword => unreachable!("crosswords have no pid"),
+        }
+        Ok(format!("https://puzz.link/p?{pid}/{width}/{height}/{body}"))
+    }
+
+    // The clues along the columns and rows, from rules `clue` picks out as `(is_column, index, count)`
+    fn outside_numbers(&self, clue: impl Fn(&Rule) -> Option<(bool, usize, u8)>) -> (Vec<Option<u8>>, Vec<Option<u8>>) {
+        let mut columns = vec![None; self.puzzle.grid.width];
+        let mut rows = vec![None; self.puzzle.grid.height];
+        for (is_column, index, count) in self.puzzle.rules.iter().filter_map(clue) {
+            let line = if is_column { &mut columns } else { &mut rows };
+            if let Some(slot) = line.get_mut(index) {
+                *slot = Some(count);
+            }
+        }
+        (columns, rows)
+    }
+}
+
+fn fill_numbers(puzzle: &mut Puzzle, numbers: &[Option<u8>]) {
+    let width = puzzle.grid.width;
+    for (index, &number) in numbers.iter().enumerate() {
+        if let Some(number) = number {
+            puzzle.set(index % width, index / width, CellContent::Number(number));
+        }
+    }
+}
+
+// Floods outwards from each cell not yet in a region, never crossing a border
+fn regions_within(width: usize, height: usize, vertical: &[bool], horizontal: &[bool]) -> Vec<Region> {
+    let mut region_of = vec![vec![None; width]; height];
+    let mut regions = Vec::new();
+    for (start_x, start_y) in (0..height).flat_map(|y| (0..width).map(move |x| (x, y))) {
+        if region_of[start_y][start_x].is_some() { continue }
+        let id = regions.len();
+        let mut cells = Vec::new();
+        let mut stack = vec![(start_x, start_y)];
+        while let Some((x, y)) = stack.pop() {
+            if region_of[y][x].is_some() { continue }
+            region_of[y][x] = Some(id);
+            cells.push((x, y));
+            if x > 0 && !vertical[y * (width - 1) + x - 1] { stack.push((x - 1, y)) }
+            if x + 1 < width && !vertical[y * (width - 1) + x] { stack.push((x + 1, y)) }
+            if y > 0 && !horizontal[(y - 1) * width + x] { stack.push((x, y - 1)) }
+            if y + 1 < height && !horizontal[y * width + x] { stack.push((x, y + 1)) }
+        }
+        cells.sort_by_key(|&(x, y)| (y, x));
+        regions.push(Region { id, cells });
+    }
+    regions
+}
+
+struct Reader<'a> {
+    body: &'a [u8],
+    at: usize,
+}
+impl Reader<'_> {
+    fn next(&mut self) -> Result<char, UrlError> {
+        let c = *self.body.get(self.at).ok_or(UrlError::TooShort)?;
+        self.at += 1;
+        Ok(c as char)
+    }
+
+    fn bad(&self) -> UrlError {
+        UrlError::BadData { at: self.at - 1, found: self.body[self.at - 1] as char }
+    }
+
+    fn hex(&mut self, digits: usize) -> Result<u32, UrlError> {
+        let mut value = 0;
+        for _ in 0..digits {
+            let digit = self.next()?.to_digit(16).ok_or_else(|| self.bad())?;
+            value = value * 16 + digit;
+        }
+        Ok(value)
+    }
+
+    // One clue, which is `Err(n)` when the next `n` cells have none
+    fn number(&mut self) -> Result<Result<u8, usize>, UrlError> {
+        let value = match self.next()? {
+            c @ ('0'..='9' | 'a'..='f') => c.to_digit(16).unwrap(),
+            '-' => self.hex(2)?,
+            c @ 'g'..='z' => return Ok(Err(c.to_digit(36).unwrap() as usize - 15)),
+            // A clue whose number is hidden, which we can't use
+            '.' => return Ok(Err(1)),
+            _ => return Err(self.bad()),
+        };
+        Ok(Ok(value as u8))
+    }
+
+    // Like pzprjs, cells past the end of the data have no clue
+    fn numbers(&mut self, count: usize) -> Result<Vec<Option<u8>>, UrlError> {
+        let mut numbers = Vec::with_capacity(count);
+        while numbers.len() < count && self.at < self.body.len() {
+            match self.number()? {
+                Ok(number) => numbers.push(Some(number)),
+                Err(skip) => numbers.extend(std::iter::repeat_n(None, skip)),
+            }
+        }
+        numbers.resize(count, None);
+        Ok(numbers)
+    }
+
+    fn outside_numbers(&mut self, width: usize, height: usize) -> Result<(Vec<Option<u8>>, Vec<Option<u8>>), UrlError> {
+        let mut numbers = self.numbers(width + height)?;
+        let rows = numbers.split_off(width);
+        Ok((numbers, rows))
+    }
+
+    fn bits(&mut self, count: usize) -> Result<Vec<bool>, UrlError> {
+        let mut bits = Vec::with_capacity(count + 4);
+        while bits.len() < count {
+            let value = self.next()?.to_digit(32).ok_or_else(|| self.bad())?;
+            bits.extend((0..5).rev().map(|bit| value & (1 << bit) != 0));
+        }
+        bits.truncate(count);
+        Ok(bits)
+    }
+
+    fn runs(&mut self) -> Result<Vec<u8>, UrlError> {
+        let mut runs = Vec::new();
+        loop {
+            match self.number()? {
+                Ok(0) => return Ok(runs),
+                Ok(run) => runs.push(run),
+                Err(_) => return Err(self.bad()),
+            }
+        }
+    }
+}
+
+fn write_number(body: &mut String, number: u8) {
+    if number < 16 {
+        body.push_str(&format!("{number:x}"));
+    } else {
+        body.push_str(&format!("-{number:02x}"));
+    }
+}
+
+fn write_numbers(body: &mut String, numbers: &[Option<u8>]) {
+    let mut skipped = 0;
+    for (index, number) in numbers.iter().enumerate() {
+        match number {
+            &Some(number) => write_number(body, number),
+            None => skipped += 1,
+        }
+        if skipped > 0 && (skipped == 20 || index + 1 == numbers.len() || numbers[index + 1].is_some()) {
+            body.push(char::from_digit(skipped + 15, 36).unwrap());
+            skipped = 0;
+        }
+    }
+}
+
+fn write_bits(body: &mut String, bits: &[bool]) {
+    for chunk in bits.chunks(5) {
+        let value = chunk.iter().enumerate().fold(0, |value, (index, &bit)| value | (bit as u32) << (4 - index));
+        body.push(char::from_digit(value, 32).unwrap());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sudoku() {
+        let definition = PuzzleDefinition::from_url("https://puzz.link/p?sudoku/4/4/1k2i3g4").unwrap();
+        let content = |x, y| definition.puzzle.grid.content(x, y).cloned();
+        assert_eq!(content(0, 0), Some(CellContent::Number(1)));
+        assert_eq!(content(1, 0), None);
+        assert_eq!(content(2, 1), Some(CellContent::Number(2)));
+        assert_eq!(content(2, 2), Some(CellContent::Number(3)));
+        assert_eq!(content(0, 3), Some(CellContent::Number(4)));
+        assert_eq!(definition.puzzle.regions[1].cells, vec![(2, 0), (3, 0), (2, 1), (3, 1)]);
+        assert_eq!(definition.puzzle.rules.len(), 12);
+        assert_eq!(definition.to_url().unwrap(), "https://puzz.link/p?sudoku/4/4/1k2i3g4i");
+    }
+
+    #[test]
+    fn six_by_six_blocks() {
+        let definition = PuzzleDefinition::from_url("p?sudoku/6/6/").unwrap();
+        assert_eq!(definition.puzzle.regions[1].cells, vec![(3, 0), (4, 0), (5, 0), (3, 1), (4, 1), (5, 1)]);
+    }
+
+    #[test]
+    fn numbers_past_fifteen_and_long_gaps() {
+        let mut body = String::new();
+        let mut numbers = vec![Some(17)];
+        numbers.extend([None; 23]);
+        numbers.push(Some(0));
+        write_numbers(&mut body, &numbers);
+        assert_eq!(body, "-11zi0");
+        assert_eq!(Reader { body: body.as_bytes(), at: 0 }.numbers(25).unwrap(), numbers);
+    }
+
+    #[test]
+    fn minesweeper() {
+        let definition = PuzzleDefinition::from_url("pzv.jp/p.html?mines/3/2/1g2i").unwrap();
+        assert!(matches!(definition.puzzle.rules[..], [Rule::AdjacentMines(0, 0, 1), Rule::AdjacentMines(2, 0, 2)]));
+        assert_eq!(definition.to_url().unwrap(), "https://puzz.link/p?mines/3/2/1g2i");
+    }
+
+    #[test]
+    fn tents() {
+        let url = "https://puzz.link/p?tents/3/2/1g1g280";
+        let definition = PuzzleDefinition::from_url(url).unwrap();
+        assert_eq!(definition.puzzle.grid.content(1, 0), Some(&CellContent::Tree));
+        assert_eq!(definition.puzzle.grid.content(1, 1), None);
+        assert!(definition.puzzle.rules.iter().any(|rule| matches!(rule, Rule::TentsInRow(1, 2))));
+        assert_eq!(definition.to_url().unwrap(), url);
+    }
+
+    #[test]
+    fn aquarium() {
+        // Two columns side by side, with a clue for the second row
+        let url = "https://puzz.link/p?aquarium/2/2/o0i2";
+        let definition = PuzzleDefinition::from_url(url).unwrap();
+        let regions = &definition.puzzle.regions;
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].cells, vec![(0, 0), (0, 1)]);
+        assert!(definition.puzzle.rules.iter().any(|rule| matches!(rule, Rule::WaterInRow(1, 2))));
+        assert_eq!(definition.to_url().unwrap(), url);
+    }
+
+    #[test]
+    fn nonogram() {
+        let url = "https://puzz.link/p?nonogram/2/2/1010200";
+        let definition = PuzzleDefinition::from_url(url).unwrap();
+        assert!(matches!(&definition.puzzle.rules[..], [
+            Rule::RunsInColumn(0, a), Rule::RunsInColumn(1, b), Rule::RunsInRow(0, c), Rule::RunsInRow(1, d),
+        ] if a == &[1] && b == &[1] && c == &[2] && d.is_empty()));
+        assert_eq!(definition.to_url().unwrap(), url);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(PuzzleDefinition::from_url("https://puzz.link/p?").unwrap_err(), UrlError::NotAPuzzle);
+        assert_eq!(PuzzleDefinition::from_url("p?slither/4/4/").unwrap_err(), UrlError::UnknownType("slither".to_string()));
+        assert_eq!(PuzzleDefinition::from_url("p?sudoku/4/4/1!").unwrap_err(), UrlError::BadData { at: 1, found: '!' });
+        assert_eq!(PuzzleDefinition::from_url("p?aquarium/4/4/00").unwrap_err(), UrlError::TooShort);
+        assert_eq!(PuzzleDefinition::from_url("p?sudoku/4/x").unwrap_err(), UrlError::BadSize("x".to_string()));
+    }
+}
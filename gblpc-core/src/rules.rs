@@ -0,0 +1,630 @@
+use crate::game::{Neighborhood, NounID};
+use crate::puzzle::dsl_valiator::{match_pairs, TentTreeMatching};
+
+/// Read access to a board of nouns, so rules can be checked against anything shaped like a grid
+pub trait Board {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn noun(&self, x: usize, y: usize) -> NounID;
+
+    fn count_in(&self, cells: &[(usize, usize)], nouns: &[NounID]) -> usize {
+        cells.iter().filter(|&&(x, y)| nouns.contains(&self.noun(x, y))).count()
+    }
+}
+
+/// Rows of noun ids, `cells[y][x]`
+impl Board for Vec<Vec<NounID>> {
+    fn width(&self) -> usize {
+        self.first().map_or(0, Vec::len)
+    }
+
+    fn height(&self) -> usize {
+        self.len()
+    }
+
+    fn noun(&self, x: usize, y: usize) -> NounID {
+        self[y][x]
+    }
+}
+
+/// A board some of whose cells are still blank, so rules can tell whether they could still hold
+pub trait PartialBoard: Board {
+    /// What a blank cell could still become, or `None` once it's decided
+    fn options(&self, x: usize, y: usize) -> Option<&[NounID]>;
+
+    /// The fewest and most of `nouns` the cells could end up holding
+    fn range_in(&self, cells: &[(usize, usize)], nouns: &[NounID]) -> (usize, usize) {
+        cells.iter().fold((0, 0), |(min, max), &(x, y)| match self.options(x, y) {
+            Some(options) => (
+                min + options.iter().all(|option| nouns.contains(option)) as usize,
+                max + options.iter().any(|option| nouns.contains(option)) as usize,
+            ),
+            None => {
+                let counted = nouns.contains(&self.noun(x, y)) as usize;
+                (min + counted, max + counted)
+            },
+        })
+    }
+}
+
+/// The groups of cells a rule is checked in, one group at a time
+#[derive(Clone, Debug)]
+pub enum Scope {
+    Board,
+    Rows,
+    Columns,
+    Region(Vec<(usize, usize)>),
+    /// Every region the game splits its board into, see `Game::regions`
+    Regions(Vec<Vec<(usize, usize)>>),
+    Row(usize),
+    Column(usize),
+}
+impl Scope {
+    pub fn groups(&self, width: usize, height: usize) -> Vec<Vec<(usize, usize)>> {
+        match self {
+            Self::Board => vec![(0..height).flat_map(|y| (0..width).map(move |x| (x, y))).collect()],
+            Self::Rows => (0..height).map(|y| (0..width).map(|x| (x, y)).collect()).collect(),
+            Self::Columns => (0..width).map(|x| (0..height).map(|y| (x, y)).collect()).collect(),
+            Self::Region(cells) => vec![cells.clone()],
+            Self::Regions(regions) => regions.clone(),
+            &Self::Row(y) => vec![(0..width).map(|x| (x, y)).collect()],
+            &Self::Column(x) => vec![(0..height).map(|y| (x, y)).collect()],
+        }
+    }
+
+    /// Where the `index`th group of `groups` is on the board
+    fn place(&self, index: usize) -> Place {
+        match self {
+            Self::Board => Place::Board,
+            Self::Rows => Place::Row(index),
+            Self::Columns => Place::Column(index),
+            Self::Region(_) => Place::Region,
+            Self::Regions(_) => Place::NumberedRegion(index),
+            &Self::Row(y) => Place::Row(y),
+            &Self::Column(x) => Place::Column(x),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Place {
+    Board,
+    Row(usize),
+    Column(usize),
+    Region,
+    /// The `index`th of the game's regions
+    NumberedRegion(usize),
+    /// The neighborhood of the cell at `(x, y)`
+    Around(usize, usize),
+}
+
+#[derive(Clone, Debug)]
+pub enum Count {
+    Exactly(usize),
+    AtLeast(usize),
+    AtMost(usize),
+    Between(usize, usize),
+    /// As many as there are of these nouns in the same group
+    SameAs(Vec<NounID>),
+}
+impl Count {
+    fn accepts(&self, n: usize, same_as: impl Fn(&[NounID]) -> usize) -> bool {
+        match self {
+            &Self::Exactly(count) => n == count,
+            &Self::AtLeast(min) => n >= min,
+            &Self::AtMost(max) => n <= max,
+            &Self::Between(min, max) => (min..=max).contains(&n),
+            Self::SameAs(nouns) => n == same_as(nouns),
+        }
+    }
+
+    /// How a count somewhere in `min..=max` fares, given the same kind of range for the nouns of `SameAs`
+    fn fit(&self, (min, max): (usize, usize), same_as: impl Fn(&[NounID]) -> (usize, usize)) -> Fit {
+        let (low, high) = match self {
+            &Self::Exactly(count) => (count, count),
+            &Self::AtLeast(count) => (count, usize::MAX),
+            &Self::AtMost(count) => (0, count),
+            &Self::Between(low, high) => (low, high),
+            Self::SameAs(nouns) => same_as(nouns),
+        };
+        if min > high {
+            Fit::TooMany
+        } else if max < low {
+            Fit::TooFew
+        } else if low <= min && max <= high && (!matches!(self, Self::SameAs(_)) || (min == max && low == high)) {
+            Fit::Always
+        } else {
+            Fit::Maybe
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Fit {
+    Always,
+    Maybe,
+    TooMany,
+    TooFew,
+}
+
+/// Whether a rule holds on a board that may still have blank cells
+#[derive(Clone, Debug)]
+pub enum Verdict {
+    Holds,
+    Open,
+    Broken(Breach),
+}
+
+/// Where and why a rule can no longer hold
+#[derive(Clone, Debug)]
+pub struct Breach {
+    pub place: Place,
+    pub cells: Vec<(usize, usize)>,
+    pub reason: Reason,
+}
+
+#[derive(Clone, Debug)]
+pub enum Reason {
+    TooMany(Vec<NounID>, Count),
+    TooFew(Vec<NounID>, Count),
+    Repeated(NounID),
+    /// Cells of the nouns with a cell of something else on or below their top row
+    Unlevel(Vec<NounID>),
+    /// Cells of the first nouns left without a cell of the second to pair with
+    Unpaired(Vec<NounID>, Vec<NounID>),
+    /// A `Not` whose rule holds
+    Negated,
+}
+
+#[derive(Clone, Debug)]
+pub enum Rule {
+    /// Every group in `scope` holds `count` of `nouns`
+    Count {
+        scope: Scope,
+        nouns: Vec<NounID>,
+        count: Count,
+    },
+    /// Every cell of `of` has `count` of `nouns` in its neighborhood
+    Adjacent {
+        of: Vec<NounID>,
+        nouns: Vec<NounID>,
+        count: Count,
+        neighborhood: Neighborhood,
+    },
+    /// No group in `scope` holds the same noun twice, only looking at `nouns` unless it's empty
+    Unique {
+        scope: Scope,
+        nouns: Vec<NounID>,
+    },
+    /// Every group in `scope` is filled with `nouns` from the bottom up to some row, like water in a tank
+    Level {
+        scope: Scope,
+        nouns: Vec<NounID>,
+    },
+    /// Every cell of `of` pairs off with a cell of `with` in its neighborhood, and every cell of `with` with one of `of`,
+    /// none of them paired twice, e.g. each tent with its own tree
+    Paired {
+        of: Vec<NounID>,
+        with: Vec<NounID>,
+        neighborhood: Neighborhood,
+    },
+    And(Vec<Rule>),
+    Or(Vec<Rule>),
+    Not(Box<Rule>),
+}
+impl Rule {
+    /// Points every `Regions` scope in the rule at these regions
+    pub fn set_regions(&mut self, regions: &[Vec<(usize, usize)>]) {
+        match self {
+            Self::Count { scope: Scope::Regions(groups), .. }
+            | Self::Unique { scope: Scope::Regions(groups), .. }
+            | Self::Level { scope: Scope::Regions(groups), .. } => {
+                *groups = regions.to_vec();
+            },
+            Self::Count { .. } | Self::Adjacent { .. } | Self::Unique { .. } | Self::Level { .. } | Self::Paired { .. } => (),
+            Self::And(rules) | Self::Or(rules) => rules.iter_mut().for_each(|rule| rule.set_regions(regions)),
+            Self::Not(rule) => rule.set_regions(regions),
+        }
+    }
+
+    pub fn holds(&self, board: &impl Board) -> bool {
+        let (width, height) = (board.width(), board.height());
+        match self {
+            Self::Count { scope, nouns, count } => scope.groups(width, height).iter().all(|group| {
+                count.accepts(board.count_in(group, nouns), |others| board.count_in(group, others))
+            }),
+            Self::Adjacent { of, nouns, count, neighborhood } => (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .filter(|&(x, y)| of.contains(&board.noun(x, y)))
+                .all(|(x, y)| {
+                    let neighbors = neighborhood.neighbors(x, y, width, height);
+                    count.accepts(board.count_in(&neighbors, nouns), |others| board.count_in(&neighbors, others))
+                }),
+            Self::Unique { scope, nouns } => scope.groups(width, height).iter().all(|group| {
+                let mut seen = Vec::new();
+                for &(x, y) in group.iter() {
+                    let noun = board.noun(x, y);
+                    if !nouns.is_empty() && !nouns.contains(&noun) { continue }
+                    if seen.contains(&noun) { return false }
+                    seen.push(noun);
+                }
+                true
+            }),
+            Self::Level { scope, nouns } => scope.groups(width, height).iter().all(|group| {
+                let top = group.iter().filter(|&&(x, y)| nouns.contains(&board.noun(x, y))).map(|&(_, y)| y).min();
+                top.is_none_or(|top| group.iter().all(|&(x, y)| y < top || nouns.contains(&board.noun(x, y))))
+            }),
+            Self::Paired { of, with, neighborhood } => {
+                let cells = |nouns: &[NounID]| cells_where(width, height, |x, y| nouns.contains(&board.noun(x, y)));
+                pair_up(cells(of), cells(with), *neighborhood, width, height).is_perfect()
+            },
+            Self::And(rules) => rules.iter().all(|rule| rule.holds(board)),
+            Self::Or(rules) => rules.iter().any(|rule| rule.holds(board)),
+            Self::Not(rule) => !rule.holds(board),
+        }
+    }
+
+    /// The cells a `Paired` rule, on its own or in an `And`, leaves without a partner, see `Game::unpaired`
+    pub fn unpaired(&self, board: &impl Board) -> Vec<(usize, usize)> {
+        let (width, height) = (board.width(), board.height());
+        match self {
+            Self::Paired { of, with, neighborhood } => {
+                let cells = |nouns: &[NounID]| cells_where(width, height, |x, y| nouns.contains(&board.noun(x, y)));
+                let (of_cells, with_cells) = (cells(of), cells(with));
+                let all_placed = of_cells.len() >= with_cells.len();
+                let matching = pair_up(of_cells, with_cells, *neighborhood, width, height);
+                let mut unpaired = matching.unmatched_trees;
+                if all_placed {
+                    unpaired.extend(matching.unmatched_tents);
+                }
+                unpaired
+            },
+            Self::And(rules) => rules.iter().flat_map(|rule| rule.unpaired(board)).collect(),
+            // Cells an `Or` or `Not` leaves unpaired needn't be wrong
+            Self::Count { .. } | Self::Adjacent { .. } | Self::Unique { .. } | Self::Level { .. } | Self::Or(_) | Self::Not(_) => Vec::new(),
+        }
+    }
+
+    /// Like `holds`, but only calls a rule broken once no way of filling the blank cells could save it
+    pub fn verdict(&self, board: &impl PartialBoard) -> Verdict {
+        let (width, height) = (board.width(), board.height());
+        let counted = |place: Place, cells: Vec<(usize, usize)>, nouns: &Vec<NounID>, count: &Count| {
+            let fit = count.fit(board.range_in(&cells, nouns), |others| board.range_in(&cells, others));
+            let reason = match fit {
+                Fit::Always => return Verdict::Holds,
+                Fit::Maybe => return Verdict::Open,
+                Fit::TooMany => Reason::TooMany(nouns.clone(), count.clone()),
+                Fit::TooFew => Reason::TooFew(nouns.clone(), count.clone()),
+            };
+            Verdict::Broken(Breach { place, cells, reason })
+        };
+        match self {
+            Self::Count { scope, nouns, count } => Verdict::all(scope.groups(width, height).into_iter()
+                .enumerate()
+                .map(|(index, group)| counted(scope.place(index), group, nouns, count))),
+            Self::Adjacent { of, nouns, count, neighborhood } => Verdict::all((0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| match board.options(x, y) {
+                    Some(options) if options.iter().any(|option| of.contains(option)) => Verdict::Open,
+                    None if of.contains(&board.noun(x, y)) => {
+                        // Counts the neighbors only, as `holds` does, but points at the cell they're around too
+                        match counted(Place::Around(x, y), neighborhood.neighbors(x, y, width, height), nouns, count) {
+                            Verdict::Broken(mut breach) => {
+                                breach.cells.push((x, y));
+                                Verdict::Broken(breach)
+                            },
+                            verdict => verdict,
+                        }
+                    },
+                    _ => Verdict::Holds,
+                })),
+            Self::Unique { scope, nouns } => Verdict::all(scope.groups(width, height).into_iter()
+                .enumerate()
+                .map(|(index, group)| {
+                    let mut seen = Vec::new();
+                    let mut open = false;
+                    for &(x, y) in group.iter() {
+                        if board.options(x, y).is_some() {
+                            open = true;
+                            continue;
+                        }
+                        let noun = board.noun(x, y);
+                        if !nouns.is_empty() && !nouns.contains(&noun) { continue }
+                        if seen.contains(&noun) {
+                            return Verdict::Broken(Breach { place: scope.place(index), cells: group, reason: Reason::Repeated(noun) });
+                        }
+                        seen.push(noun);
+                    }
+                    if open { Verdict::Open } else { Verdict::Holds }
+                })),
+            Self::Level { scope, nouns } => Verdict::all(scope.groups(width, height).into_iter()
+                .enumerate()
+                .map(|(index, group)| {
+                    let (mut filled, mut empty) = (Vec::new(), Vec::new());
+                    for &(x, y) in group.iter() {
+                        match board.range_in(&[(x, y)], nouns) {
+                            (1, _) => filled.push((x, y)),
+                            (_, 0) => empty.push((x, y)),
+                            _ => (),
+                        }
+                    }
+                    // A cell that must be empty on or below one that must be filled can't be saved
+                    let spill = filled.iter()
+                        .find_map(|&(fx, fy)| empty.iter().find(|&&(_, ey)| ey >= fy).map(|&empty| vec![(fx, fy), empty]));
+                    match spill {
+                        Some(cells) => Verdict::Broken(Breach { place: scope.place(index), cells, reason: Reason::Unlevel(nouns.clone()) }),
+                        None if filled.len() + empty.len() < group.len() => Verdict::Open,
+                        None => Verdict::Holds,
+                    }
+                })),
+            Self::Paired { of, with, neighborhood } => {
+                // Cells that are one of `nouns`, and those that are or could still become one
+                let cells = |nouns: &[NounID]| {
+                    let sure = cells_where(width, height, |x, y| board.options(x, y).is_none() && nouns.contains(&board.noun(x, y)));
+                    let maybe = cells_where(width, height, |x, y| board.range_in(&[(x, y)], nouns).1 == 1);
+                    (sure, maybe)
+                };
+                let ((of_sure, of_maybe), (with_sure, with_maybe)) = (cells(of), cells(with));
+                let decided = of_sure.len() == of_maybe.len() && with_sure.len() == with_maybe.len();
+                let unpaired = |cells: Vec<(usize, usize)>, nouns: &Vec<NounID>, others: &Vec<NounID>| Verdict::Broken(Breach {
+                    place: Place::Board,
+                    cells,
+                    reason: Reason::Unpaired(nouns.clone(), others.clone()),
+                });
+                let of_side = pair_up(of_sure.clone(), with_maybe, *neighborhood, width, height);
+                let with_side = pair_up(with_sure.clone(), of_maybe, *neighborhood, width, height);
+                if !of_side.unmatched_trees.is_empty() {
+                    unpaired(of_side.unmatched_trees, of, with)
+                } else if !with_side.unmatched_trees.is_empty() {
+                    unpaired(with_side.unmatched_trees, with, of)
+                } else if !decided {
+                    Verdict::Open
+                } else {
+                    // With nothing left to decide, both sides have to pair up at once
+                    let matching = pair_up(of_sure, with_sure, *neighborhood, width, height);
+                    if matching.is_perfect() { return Verdict::Holds }
+                    unpaired([matching.unmatched_trees, matching.unmatched_tents].concat(), of, with)
+                }
+            },
+            Self::And(rules) => Verdict::all(rules.iter().map(|rule| rule.verdict(board))),
+            Self::Or(rules) => {
+                let mut breach = None;
+                for verdict in rules.iter().map(|rule| rule.verdict(board)) {
+                    match verdict {
+                        Verdict::Holds => return Verdict::Holds,
+                        Verdict::Open => breach = Some(None),
+                        Verdict::Broken(broken) => { breach.get_or_insert(Some(broken)); },
+                    }
+                }
+                match breach {
+                    Some(Some(broken)) => Verdict::Broken(broken),
+                    Some(None) => Verdict::Open,
+                    // An empty `Or` never holds
+                    None => Verdict::Broken(Breach { place: Place::Board, cells: Vec::new(), reason: Reason::Negated }),
+                }
+            },
+            Self::Not(rule) => match rule.verdict(board) {
+                Verdict::Holds => Verdict::Broken(Breach { place: Place::Board, cells: Vec::new(), reason: Reason::Negated }),
+                Verdict::Open => Verdict::Open,
+                Verdict::Broken(_) => Verdict::Holds,
+            },
+        }
+    }
+}
+
+// Every `(x, y)` the predicate picks, row by row
+fn cells_where(width: usize, height: usize, picks: impl Fn(usize, usize) -> bool) -> Vec<(usize, usize)> {
+    (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).filter(|&(x, y)| picks(x, y)).collect()
+}
+
+// The most cells of `of` paired off with cells of `with` in their neighborhood, `of` taking the trees' side
+fn pair_up(of: Vec<(usize, usize)>, with: Vec<(usize, usize)>, neighborhood: Neighborhood, width: usize, height: usize) -> TentTreeMatching {
+    match_pairs(of, with, |(x, y), other| neighborhood.neighbors(x, y, width, height).contains(&other))
+}
+
+impl Verdict {
+    // The first breach, or `Holds` only when every verdict does
+    fn all(verdicts: impl Iterator<Item = Verdict>) -> Self {
+        let mut all = Self::Holds;
+        for verdict in verdicts {
+            match verdict {
+                Self::Broken(_) => return verdict,
+                Self::Open => all = Self::Open,
+                Self::Holds => (),
+            }
+        }
+        all
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `None` is a blank cell that could still become either of `options`
+    struct Partial {
+        cells: Vec<Vec<Option<NounID>>>,
+        options: Vec<NounID>,
+    }
+    impl Board for Partial {
+        fn width(&self) -> usize { self.cells[0].len() }
+        fn height(&self) -> usize { self.cells.len() }
+        fn noun(&self, x: usize, y: usize) -> NounID { self.cells[y][x].unwrap_or(0) }
+    }
+    impl PartialBoard for Partial {
+        fn options(&self, x: usize, y: usize) -> Option<&[NounID]> {
+            self.cells[y][x].is_none().then_some(self.options.as_slice())
+        }
+    }
+
+    fn partial(cells: Vec<Vec<Option<NounID>>>) -> Partial {
+        Partial { cells, options: vec![1, 2] }
+    }
+
+    fn ones(scope: Scope, count: Count) -> Rule {
+        Rule::Count { scope, nouns: vec![1], count }
+    }
+
+    #[test]
+    fn scopes_split_the_board_into_groups() {
+        let groups = |scope: Scope| scope.groups(3, 2);
+        assert_eq!(groups(Scope::Board), vec![vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]]);
+        assert_eq!(groups(Scope::Rows), vec![vec![(0, 0), (1, 0), (2, 0)], vec![(0, 1), (1, 1), (2, 1)]]);
+        assert_eq!(groups(Scope::Columns), vec![vec![(0, 0), (0, 1)], vec![(1, 0), (1, 1)], vec![(2, 0), (2, 1)]]);
+        assert_eq!(groups(Scope::Row(1)), vec![vec![(0, 1), (1, 1), (2, 1)]]);
+        assert_eq!(groups(Scope::Column(2)), vec![vec![(2, 0), (2, 1)]]);
+        assert_eq!(groups(Scope::Region(vec![(1, 1), (2, 0)])), vec![vec![(1, 1), (2, 0)]]);
+        assert_eq!(groups(Scope::Regions(vec![vec![(0, 0)], vec![(1, 0)]])), vec![vec![(0, 0)], vec![(1, 0)]]);
+        assert_eq!(Scope::Rows.place(1), Place::Row(1));
+        assert_eq!(Scope::Column(2).place(0), Place::Column(2));
+        assert_eq!(Scope::Regions(Vec::new()).place(3), Place::NumberedRegion(3));
+    }
+
+    #[test]
+    fn counts_hold_in_every_group_of_their_scope() {
+        // Rows hold two 1s and one, columns one, none and two
+        let board = vec![vec![1, 2, 1], vec![2, 2, 1]];
+        let holds = |scope, count| ones(scope, count).holds(&board);
+        assert!(holds(Scope::Board, Count::Exactly(3)));
+        assert!(!holds(Scope::Board, Count::Exactly(2)));
+        assert!(holds(Scope::Rows, Count::AtLeast(1)));
+        assert!(!holds(Scope::Rows, Count::AtLeast(2)));
+        assert!(holds(Scope::Columns, Count::AtMost(2)));
+        assert!(!holds(Scope::Columns, Count::AtMost(1)));
+        assert!(holds(Scope::Row(0), Count::Between(2, 3)));
+        assert!(!holds(Scope::Row(1), Count::Between(2, 3)));
+        assert!(holds(Scope::Column(1), Count::Exactly(0)));
+        assert!(holds(Scope::Region(vec![(0, 0), (2, 1)]), Count::Exactly(2)));
+        assert!(!holds(Scope::Regions(vec![vec![(0, 0)], vec![(1, 0)]]), Count::Exactly(1)));
+        assert!(holds(Scope::Column(0), Count::SameAs(vec![2])));
+        assert!(!holds(Scope::Rows, Count::SameAs(vec![2])));
+    }
+
+    #[test]
+    fn counts_stay_open_until_no_blanks_could_save_them() {
+        let verdict = |row: Vec<Option<NounID>>, count| ones(Scope::Rows, count).verdict(&partial(vec![row]));
+        // One 1 and two blanks could hold one to three
+        let row = || vec![Some(1), None, None];
+        assert!(matches!(verdict(row(), Count::Exactly(1)), Verdict::Open));
+        assert!(matches!(verdict(row(), Count::AtLeast(1)), Verdict::Holds));
+        assert!(matches!(verdict(row(), Count::AtMost(3)), Verdict::Holds));
+        assert!(matches!(verdict(row(), Count::Between(2, 5)), Verdict::Open));
+        assert!(matches!(verdict(row(), Count::SameAs(vec![2])), Verdict::Open));
+        let Verdict::Broken(breach) = verdict(row(), Count::Exactly(0)) else { panic!("a 1 already") };
+        assert_eq!(breach.place, Place::Row(0));
+        assert_eq!(breach.cells, vec![(0, 0), (1, 0), (2, 0)]);
+        assert!(matches!(breach.reason, Reason::TooMany(..)));
+        assert!(matches!(verdict(vec![Some(2), Some(2), None], Count::AtLeast(2)), Verdict::Broken(Breach { reason: Reason::TooFew(..), .. })));
+        assert!(matches!(verdict(vec![Some(1), Some(1), Some(2)], Count::SameAs(vec![2])), Verdict::Broken(_)));
+        assert!(matches!(verdict(vec![Some(1), Some(2), None], Count::Between(1, 2)), Verdict::Holds));
+    }
+
+    #[test]
+    fn unique_repeats_are_broken_even_with_blanks_left() {
+        let rows = Rule::Unique { scope: Scope::Rows, nouns: Vec::new() };
+        let ones = Rule::Unique { scope: Scope::Board, nouns: vec![1] };
+        assert!(rows.holds(&vec![vec![1, 2], vec![2, 1]]));
+        assert!(!rows.holds(&vec![vec![1, 1], vec![2, 1]]));
+        assert!(ones.holds(&vec![vec![1, 2], vec![2, 2]]));
+        assert!(!ones.holds(&vec![vec![1, 2], vec![2, 1]]));
+        let board = partial(vec![vec![Some(1), None], vec![Some(2), Some(1)]]);
+        assert!(matches!(rows.verdict(&board), Verdict::Open));
+        let Verdict::Broken(breach) = ones.verdict(&board) else { panic!("two 1s") };
+        assert_eq!(breach.place, Place::Board);
+        assert!(matches!(breach.reason, Reason::Repeated(1)));
+        assert!(matches!(rows.verdict(&partial(vec![vec![Some(1), Some(2)], vec![Some(2), Some(1)]])), Verdict::Holds));
+    }
+
+    #[test]
+    fn combinators_follow_their_rules() {
+        let none = || ones(Scope::Board, Count::Exactly(0));
+        let some = || ones(Scope::Board, Count::AtLeast(1));
+        let board = vec![vec![1, 2]];
+        assert!(Rule::Or(vec![none(), some()]).holds(&board));
+        assert!(!Rule::And(vec![none(), some()]).holds(&board));
+        assert!(Rule::Not(Box::new(none())).holds(&board));
+        let blank = partial(vec![vec![None, Some(2)]]);
+        assert!(matches!(Rule::And(vec![none(), some()]).verdict(&blank), Verdict::Open));
+        assert!(matches!(Rule::Not(Box::new(none())).verdict(&blank), Verdict::Open));
+        let decided = partial(vec![vec![Some(2), Some(2)]]);
+        assert!(matches!(Rule::Or(vec![some(), none()]).verdict(&decided), Verdict::Holds));
+        assert!(matches!(Rule::And(vec![none(), some()]).verdict(&decided), Verdict::Broken(Breach { reason: Reason::TooFew(..), .. })));
+        assert!(matches!(Rule::Not(Box::new(none())).verdict(&decided), Verdict::Broken(Breach { reason: Reason::Negated, .. })));
+        assert!(matches!(Rule::Or(Vec::new()).verdict(&decided), Verdict::Broken(_)));
+    }
+
+    // 1 is water, 2 is dry, in one tank of two columns
+    fn tank() -> Rule {
+        Rule::Level { scope: Scope::Regions(vec![(0..3).flat_map(|y| [(0, y), (1, y)]).collect()]), nouns: vec![1] }
+    }
+
+    #[test]
+    fn water_fills_tanks_from_the_bottom() {
+        assert!(tank().holds(&vec![vec![2, 2], vec![1, 1], vec![1, 1]]));
+        assert!(tank().holds(&vec![vec![2, 2]; 3]));
+        assert!(!tank().holds(&vec![vec![2, 2], vec![1, 2], vec![1, 1]]));
+        assert!(!tank().holds(&vec![vec![1, 1], vec![2, 2], vec![1, 1]]));
+    }
+
+    #[test]
+    fn water_over_a_dry_cell_is_broken() {
+        let verdict = |cells| tank().verdict(&Partial { cells, options: vec![1, 2] });
+        assert!(matches!(verdict(vec![vec![None, None], vec![Some(1), None], vec![None, None]]), Verdict::Open));
+        let Verdict::Broken(breach) = verdict(vec![vec![None, None], vec![Some(1), None], vec![None, Some(2)]]) else {
+            panic!("dry cell under water");
+        };
+        assert_eq!(breach.place, Place::NumberedRegion(0));
+        assert_eq!(breach.cells, vec![(0, 1), (1, 2)]);
+        assert!(matches!(verdict(vec![vec![Some(2), None], vec![Some(1), Some(1)], vec![Some(1), Some(1)]]), Verdict::Open));
+    }
+
+    #[test]
+    fn adjacent_counts_the_neighbors_but_not_the_cell() {
+        // No 1 orthogonally next to another
+        let apart = Rule::Adjacent { of: vec![1], nouns: vec![1], count: Count::Exactly(0), neighborhood: Neighborhood::Orthogonal };
+        assert!(apart.holds(&vec![vec![1, 2], vec![2, 1]]));
+        assert!(!apart.holds(&vec![vec![1, 1], vec![2, 2]]));
+        let verdict = |cells| apart.verdict(&Partial { cells, options: vec![1, 2] });
+        assert!(matches!(verdict(vec![vec![Some(1), None], vec![None, None]]), Verdict::Open));
+        assert!(matches!(verdict(vec![vec![Some(1), Some(2)], vec![Some(2), Some(1)]]), Verdict::Holds));
+        let Verdict::Broken(breach) = verdict(vec![vec![Some(1), Some(1)], vec![None, None]]) else {
+            panic!("neighboring 1s");
+        };
+        assert_eq!(breach.place, Place::Around(0, 0));
+        assert_eq!(breach.cells, vec![(1, 0), (0, 1), (0, 0)]);
+    }
+
+    #[test]
+    fn paired_cells_each_need_their_own_partner() {
+        // 1s are tents and 2s trees; both trees share the first tent, and the other two tents share the last tree
+        let paired = Rule::Paired { of: vec![1], with: vec![2], neighborhood: Neighborhood::Orthogonal };
+        let adjacent_both_ways = Rule::And(vec![
+            Rule::Adjacent { of: vec![1], nouns: vec![2], count: Count::AtLeast(1), neighborhood: Neighborhood::Orthogonal },
+            Rule::Adjacent { of: vec![2], nouns: vec![1], count: Count::AtLeast(1), neighborhood: Neighborhood::Orthogonal },
+        ]);
+        let crowded = vec![vec![2, 1, 2, 0, 1, 2, 1]];
+        assert!(adjacent_both_ways.holds(&crowded));
+        assert!(!paired.holds(&crowded));
+        assert!(paired.holds(&vec![vec![2, 1, 0, 1, 2]]));
+
+        // Blanks could still be tents, but not trees
+        let verdict = |row| paired.verdict(&Partial { cells: vec![row], options: vec![0, 1] });
+        assert!(matches!(verdict(vec![Some(2), None]), Verdict::Open));
+        assert!(matches!(verdict(vec![Some(2), Some(1)]), Verdict::Holds));
+        let Verdict::Broken(breach) = verdict(vec![Some(2), Some(0), None]) else { panic!("the tree can't get a tent") };
+        assert_eq!(breach.cells, vec![(0, 0)]);
+        assert!(matches!(breach.reason, Reason::Unpaired(..)));
+        let Verdict::Broken(breach) = verdict(crowded.concat().into_iter().map(Some).collect()) else { panic!("a tent without a tree") };
+        assert_eq!(breach.cells, vec![(6, 0)]);
+    }
+
+    #[test]
+    fn unpaired_trees_only_count_once_every_tent_is_down() {
+        let paired = Rule::Paired { of: vec![1], with: vec![2], neighborhood: Neighborhood::Orthogonal };
+        // A tent far from any tree is unpaired from the start, while the trees wait for their tents
+        assert_eq!(paired.unpaired(&vec![vec![2, 0, 0, 1, 0, 2]]), vec![(3, 0)]);
+        assert_eq!(paired.unpaired(&vec![vec![2, 1, 0, 0, 0, 2]]), Vec::new());
+        assert_eq!(paired.unpaired(&vec![vec![2, 1, 2, 0, 1, 0]]), vec![(4, 0), (2, 0)]);
+        assert!(paired.unpaired(&vec![vec![2, 1, 0, 0, 1, 2]]).is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+//! The engine for web pages that want to check and generate puzzles without the Bevy canvas. Seeds are `BigInt`s on
+//! the JavaScript side, as they're `u64` everywhere else.
+
+use gblpc_core::builders::parse_games;
+use gblpc_core::game::Game;
+use gblpc_core::play::{Click, Play};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// A catalogue game and the board being played on it
+#[wasm_bindgen]
+pub struct Puzzle {
+    game: Game,
+    seed: Option<u64>,
+    play: Play,
+}
+
+/// What `toJson` gives, with cells by noun name as the command line writes them
+#[derive(Serialize)]
+struct PuzzleJson<'a> {
+    game: &'a str,
+    seed: Option<u64>,
+    cells: Vec<Vec<&'a str>>,
+    /// Each track's clues, in the order the catalogue lists the tracks
+    clues: Vec<&'a [Option<usize>]>,
+    solved: bool,
+}
+
+/// Builds the game called `name` from the text of a `*.games.ron` catalogue, starting from a board of its default cell
+#[wasm_bindgen(js_name = loadGame)]
+pub fn load_game(catalogue: &str, name: &str) -> Result<Puzzle, JsError> {
+    Puzzle::load(catalogue, name).map_err(|error| JsError::new(&error))
+}
+
+#[wasm_bindgen]
+impl Puzzle {
+    /// Replaces the board with the one `seed` generates, the same one the app and the command line give, throwing when
+    /// it can't place every cell the catalogue asks for
+    pub fn generate(&mut self, seed: u64) -> Result<(), JsError> {
+        self.regenerate(seed).map_err(|error| JsError::new(&error))
+    }
+
+    /// Runs the cell's action for a `MouseEvent.button`, 0 for a left click and 2 for a right one, returning whether
+    /// there was one to run
+    #[wasm_bindgen(js_name = applyAction)]
+    pub fn apply_action(&mut self, x: usize, y: usize, button: i16) -> bool {
+        let click = match button {
+            0 => Click::Left,
+            2 => Click::Right,
+            _ => return false,
+        };
+        if x >= self.game.width || y >= self.game.height { return false }
+        // Like the app, a generated no-guess board is drawn again around the first reveal, or kept if it can't be
+        if let Some(seed) = self.seed {
+            let _ = self.game.redraw_for_first_reveal(&mut self.play, seed, (x, y), click);
+        }
+        self.play.click(&self.game, x, y, click)
+    }
+
+    #[wasm_bindgen(js_name = isSolved)]
+    pub fn is_solved(&self) -> bool {
+        self.game.is_solved(&self.play)
+    }
+
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        let nouns = &self.game.nouns.nouns;
+        let json = PuzzleJson {
+            game: &self.game.name,
+            seed: self.seed,
+            cells: self.play.cells.iter().map(|row| row.iter().map(|&noun_id| nouns[noun_id].name()).collect()).collect(),
+            clues: self.game.tracks.iter().map(|track| &track.clues[..]).collect(),
+            solved: self.is_solved(),
+        };
+        serde_json::to_string(&json).unwrap_or_default()
+    }
+}
+
+impl Puzzle {
+    // Apart from `load_game` so errors can be tested off the web, where a `JsError` can't be made
+    fn load(catalogue: &str, name: &str) -> Result<Self, String> {
+        let game_builders = parse_games(catalogue).map_err(|error| error.to_string())?;
+        let game_builder = game_builders.into_iter()
+            .find(|game_builder| game_builder.name == name)
+            .ok_or_else(|| format!("no game named `{name}`"))?;
+        let mut game = game_builder.to_game().map_err(|error| error.to_string())?;
+        let play = Play::new(vec![vec![game.nouns.gen_config.default; game.width]; game.height]);
+        game.fill_tracks(&play);
+        Ok(Self { game, seed: None, play })
+    }
+
+    fn regenerate(&mut self, seed: u64) -> Result<(), String> {
+        let cells = self.game.generate(seed).map_err(|shortfall| shortfall.to_string())?;
+        self.seed = Some(seed);
+        self.play = Play::new(cells);
+        self.game.fill_tracks(&self.play);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CATALOGUE: &str = include_str!("../../assets/games/default.games.ron");
+
+    #[test]
+    fn generates_and_plays_like_the_app() {
+        let mut puzzle = Puzzle::load(CATALOGUE, "Latin Square").unwrap();
+        puzzle.regenerate(7).unwrap();
+        let generated = puzzle.to_json();
+        assert!(generated.starts_with(r#"{"game":"Latin Square","seed":7,"cells":[["#));
+        let (x, y) = (0..puzzle.game.height)
+            .flat_map(|y| (0..puzzle.game.width).map(move |x| (x, y)))
+            .find(|&(x, y)| puzzle.game.nouns.nouns[puzzle.play.cells[y][x]].l_click().is_some())
+            .unwrap();
+        assert!(puzzle.apply_action(x, y, 0));
+        assert_ne!(puzzle.to_json(), generated);
+        assert!(!puzzle.apply_action(puzzle.game.width, 0, 0));
+        assert!(!puzzle.apply_action(x, y, 1));
+        puzzle.regenerate(7).unwrap();
+        assert_eq!(puzzle.to_json(), generated);
+    }
+
+    #[test]
+    fn unknown_games_are_errors() {
+        assert_eq!(Puzzle::load(CATALOGUE, "Chess").err().unwrap(), "no game named `Chess`");
+        assert!(Puzzle::load("[", "Chess").is_err());
+    }
+}
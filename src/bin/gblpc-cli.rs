@@ -0,0 +1,124 @@
+//! Checks, generates, solves and rates games from a catalogue without opening a window
+
+use gblpc::headless::{check, BoardFile, Format, Games, HeadlessError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+usage: gblpc-cli [--catalogue FILE]... [--json] COMMAND
+
+commands:
+  check [FILE]...                          report schema and reference errors in catalogues
+  generate --game NAME [--seed S] [--count N]
+                                           print N boards from consecutive seeds
+  solve FILE                               apply hints to a board until it's solved or stuck
+  rate FILE                                rate a board by the hardest technique, or hint, it needs
+
+FILE holds one board or a list of them, such as `generate` prints, as JSON for `.json` files and RON
+otherwise. Output is RON unless `--json` is given. The catalogue defaults to assets/games/default.games.ron.";
+
+const DEFAULT_CATALOGUE: &str = "assets/games/default.games.ron";
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut catalogues = Vec::new();
+    let mut format = Format::Ron;
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--catalogue" => catalogues.push(PathBuf::from(args.next().ok_or("--catalogue needs a file")?)),
+            "--json" => format = Format::Json,
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return Ok(());
+            },
+            _ => rest.push(arg),
+        }
+    }
+    if catalogues.is_empty() {
+        catalogues.push(PathBuf::from(DEFAULT_CATALOGUE));
+    }
+    let Some((command, args)) = rest.split_first() else { return Err(USAGE.to_string()) };
+
+    match command.as_str() {
+        "check" => {
+            let files = if args.is_empty() { catalogues } else { args.iter().map(PathBuf::from).collect() };
+            let mut failed = false;
+            for file in files.iter() {
+                match check(file) {
+                    Ok(games) => println!("{}: {games} games ok", file.display()),
+                    Err(error) => {
+                        eprintln!("{error}");
+                        failed = true;
+                    },
+                }
+            }
+            if failed { Err("catalogue check failed".to_string()) } else { Ok(()) }
+        },
+        "generate" => {
+            let (mut game, mut seed, mut count) = (None, None, 1);
+            let mut args = args.iter();
+            while let Some(arg) = args.next() {
+                let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+                match arg.as_str() {
+                    "--game" => game = Some(value()?.clone()),
+                    "--seed" => seed = Some(value()?.parse::<u64>().map_err(|error| format!("--seed: {error}"))?),
+                    "--count" => count = value()?.parse::<usize>().map_err(|error| format!("--count: {error}"))?,
+                    _ => return Err(format!("unexpected `{arg}`\n\n{USAGE}")),
+                }
+            }
+            let game = game.ok_or("generate needs --game NAME")?;
+            let games = Games::load(&catalogues).map_err(show)?;
+            let boards = games.generate(&game, seed.unwrap_or_else(rand::random), count).map_err(show)?;
+            print(format, &boards)
+        },
+        "solve" | "rate" => {
+            let [file] = args else { return Err(format!("{command} needs one FILE\n\n{USAGE}")) };
+            let (boards, listed) = read_boards(Path::new(file))?;
+            let games = Games::load(&catalogues).map_err(show)?;
+            if command == "solve" {
+                let solves = boards.iter().map(|board| games.solve(board)).collect::<Result<Vec<_>, _>>().map_err(show)?;
+                if listed { print(format, &solves) } else { print(format, &solves[0]) }
+            } else {
+                let rates = boards.iter().map(|board| games.rate(board)).collect::<Result<Vec<_>, _>>().map_err(show)?;
+                if listed { print(format, &rates) } else { print(format, &rates[0]) }
+            }
+        },
+        _ => Err(format!("unknown command `{command}`\n\n{USAGE}")),
+    }
+}
+
+fn show(error: HeadlessError) -> String {
+    error.to_string()
+}
+
+// The boards in the file, and whether they came as a list
+fn read_boards(path: &Path) -> Result<(Vec<BoardFile>, bool), String> {
+    let source = std::fs::read_to_string(path).map_err(|error| format!("could not read `{}`: {error}", path.display()))?;
+    let format = Format::of(path);
+    match format.read::<BoardFile>(&source) {
+        Ok(board) => Ok((vec![board], false)),
+        Err(error) => format.read::<Vec<BoardFile>>(&source)
+            .map(|boards| (boards, true))
+            .map_err(|_| format!("{}: {error}", path.display())),
+    }
+}
+
+// A reader that stops early, like `head`, isn't an error
+fn print(format: Format, value: &impl serde::Serialize) -> Result<(), String> {
+    match writeln!(std::io::stdout(), "{}", format.write(value).map_err(show)?) {
+        Err(error) if error.kind() != std::io::ErrorKind::BrokenPipe => Err(error.to_string()),
+        _ => Ok(()),
+    }
+}
@@ -2,103 +2,110 @@ use bevy::prelude::*;
 use bevy::text::BreakLineOn;
 use serde::{Serialize, Deserialize};
 use serde_inline_default::serde_inline_default;
-use std::collections::HashMap;
-use std::cmp::Ordering;
+use gblpc_core::builders::{self, ActionBuilder, BuildError, ForbidAdjacentBuilder, GenMethodBuilder, PuzzLinkBuilder, RegionsBuilder, RevealBuilder, RuleBuilder, TrackBuilder};
 use crate::g::*;
 use crate::utils::*;
+use crate::catalogue::*;
 
-#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde_inline_default]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub(super) struct GameConfigBuilder {
     name: String,
+    #[serde_inline_default(10)]
+    width: usize,
+    #[serde_inline_default(10)]
+    height: usize,
     noun_builder: NounBuilder,
+    #[serde(default)]
+    rules: Vec<RuleBuilder>,
+    #[serde(default)]
+    regions: RegionsBuilder,
+    #[serde(default)]
+    tracks: Vec<TrackBuilder>,
+    #[serde(default)]
+    puzz_link: Option<PuzzLinkBuilder>,
 }
+// Builders consume themselves on conversion, named `to_*` like the `gblpc-core` builders they mirror
+#[allow(clippy::wrong_self_convention)]
 impl GameConfigBuilder {
-    pub(super) fn to_game_config(self, asset_server: AssetServer) -> GameConfig {
-        let Self { name, noun_builder } = self;
-        GameConfig {
-            name,
-            nouns: noun_builder.to_nouns(asset_server),
-        }
+    /// Builds the game through `gblpc-core`, which checks it as `validate` does, and how its cells look
+    pub(super) fn to_game_config(self, asset_server: AssetServer) -> Result<GameConfig, BuildError> {
+        let game = self.game_builder().to_game()?;
+        let looks = self.noun_builder.spatial_element_builder.cells.into_iter()
+            .map(|cell| cell.to_look(asset_server.clone()))
+            .collect();
+        Ok(GameConfig { game, looks })
     }
-}
 
-#[derive(Serialize, Deserialize, Default, Debug)]
-struct NounBuilder {
-    spatial_element_builder: SpatialElementBuilder,
-}
-impl NounBuilder {
-    fn to_nouns(self, asset_server: AssetServer) -> Nouns {
-        let mut nouns = Vec::new();
-        let Self { spatial_element_builder } = self;
-        let SpatialElementBuilder { cells } = spatial_element_builder;
-        let mut noun_id = 0;
-        let mut noun_map = HashMap::new();
-        let mut gen_config = GenConfig::default();
-        for cell in cells.iter() {
-            match cell.gen_method {
-                GenMethodBuilder::None => (),
-                GenMethodBuilder::Default => gen_config.default = noun_id,
-                GenMethodBuilder::Random(mut prob) => {
-                    if let Some((last_prob, _)) = gen_config.probs.last() {
-                        prob += last_prob;
-                    }
-                    gen_config.probs.push((prob, noun_id));
+    /// What the engine reads of this game, leaving out how its cells look
+    pub(super) fn game_builder(&self) -> builders::GameBuilder {
+        builders::GameBuilder {
+            name: self.name.clone(),
+            width: self.width,
+            height: self.height,
+            noun_builder: builders::NounBuilder {
+                spatial_element_builder: builders::SpatialElementBuilder {
+                    cells: self.noun_builder.spatial_element_builder.cells.iter().map(CellBuilder::engine).collect(),
                 },
-            }
-            noun_map.insert(cell.name.clone(), noun_id);
-            noun_id += 1;
+            },
+            rules: self.rules.clone(),
+            regions: self.regions.clone(),
+            tracks: self.tracks.clone(),
+            puzz_link: self.puzz_link.clone(),
         }
-        let (last_prob, is_greater) = if let Some((last_prob, _)) = gen_config.probs.last() {
-            
-            match f64::partial_cmp(last_prob, &1.0) {
-                Some(Ordering::Greater) => (*last_prob, true),
-                _ => (*last_prob, false),
+    }
+
+    /// Every error `gblpc-core` finds in this game, plus colors it can't look up
+    pub(super) fn validate(&self, locator: &SourceLocator) -> Vec<GameConfigError> {
+        let game_builder = self.game_builder();
+        let mut errors = game_builder.validate().into_iter()
+            .map(|invalid| locator.error(&invalid.anchors, invalid.cell, GameConfigErrorKind::Build(invalid.error)))
+            .collect::<Vec<_>>();
+        for cell in self.noun_builder.spatial_element_builder.cells.iter() {
+            let mut colors = vec![("background_color", &cell.background_color), ("border_color", &cell.border_color)];
+            if let Some(text) = &cell.text {
+                colors.extend(text.section_builders.iter().map(|section| ("color", &section.style_builder.color)));
             }
-        } else { (0.0, false) };
-        if is_greater {
-            for (prob, _) in gen_config.probs.iter_mut() {
-                *prob /= last_prob;
+            for (key, color) in colors {
+                if try_str_to_css_srgba(color).is_none() {
+                    let anchors = [Anchor::Value("name", &self.name), Anchor::Value("name", &cell.name), Anchor::Value(key, color)];
+                    errors.push(locator.error(&anchors, Some(&cell.name), GameConfigErrorKind::UnknownColor {
+                        cell: cell.name.clone(),
+                        color: color.clone(),
+                    }));
+                }
             }
         }
-        for cell in cells {
-            nouns.push(cell.to_noun(asset_server.clone(), &noun_map));
-        }
-        Nouns {
-            nouns,
-            noun_map,
-            gen_config,
-        }
+        errors
     }
-}
 
-#[derive(Serialize, Deserialize, Default, Debug)]
-struct SpatialElementBuilder {
-    cells: Vec<CellBuilder>,
+    pub(super) fn name(&self) -> &str {
+        &self.name
+    }
 }
 
-#[derive(Serialize, Deserialize, Default, Debug)]
-pub(super) enum GenMethodBuilder {
-    #[default]
-    None,
-    Default,
-    Random(f64),
-    // DependOn(String),
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+struct NounBuilder {
+    spatial_element_builder: SpatialElementBuilder,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-enum ActionBuilder {
-    TransformTo(String),
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+struct SpatialElementBuilder {
+    cells: Vec<CellBuilder>,
 }
 
 #[serde_inline_default]
-#[derive(Serialize, Deserialize, Default, Debug)]
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
 #[serde(default)]
 struct CellBuilder {
     name: String,
     l_click: Option<ActionBuilder>,
     r_click: Option<ActionBuilder>,
-    // valid: 
+    reveal: Option<RevealBuilder>,
     gen_method: GenMethodBuilder,
+    forbid_adjacent: Option<ForbidAdjacentBuilder>,
+    blank: bool,
+    counts_as: Option<String>,
     style: Style,
     #[serde_inline_default("WHITE".to_string())]
     background_color: String,
@@ -108,42 +115,34 @@ struct CellBuilder {
     text: Option<TextBuilder>,
 }
 // impl Builder for CellBuilder {
+#[allow(clippy::wrong_self_convention)]
 impl CellBuilder {
-    fn to_noun(self, asset_server: AssetServer, noun_map: &HashMap<String, usize>) -> Noun {
-        let Self { name, l_click, r_click, style, background_color, border_color, border_radius, text: text_ops, .. } = self;
-        Noun::SpatialElement(
-            SpatialElement::Cell {
-                id: *noun_map.get(&name).unwrap(),
-                name,
-                l_click: match l_click {
-                    Some(ActionBuilder::TransformTo(next)) => {
-                        Some(Action::TransformTo(*noun_map.get(&next).unwrap()))
-                    },
-                    _ => None,
-                },
-                r_click: match r_click {
-                    Some(ActionBuilder::TransformTo(next)) => {
-                        Some(Action::TransformTo(*noun_map.get(&next).unwrap()))
-                    },
-                    _ => None,
-                },
-                style,
-                background_color: BackgroundColor(Color::Srgba(str_to_css_srgba(&background_color))),
-                border_color: BorderColor(Color::Srgba(str_to_css_srgba(&border_color))),
-                border_radius,
-                text: if let Some(text) = text_ops { Some(text.to_text(asset_server)) } else { None },
-            }
-        )
+    /// What the cell does, for the engine
+    fn engine(&self) -> builders::CellBuilder {
+        let Self { name, l_click, r_click, reveal, gen_method, forbid_adjacent, blank, counts_as, .. } = self.clone();
+        builders::CellBuilder { name, l_click, r_click, reveal, gen_method, forbid_adjacent, blank, counts_as }
+    }
+
+    fn to_look(self, asset_server: AssetServer) -> Look {
+        let Self { style, background_color, border_color, border_radius, text: text_ops, .. } = self;
+        Look {
+            style,
+            background_color: BackgroundColor(Color::Srgba(str_to_css_srgba(&background_color))),
+            border_color: BorderColor(Color::Srgba(str_to_css_srgba(&border_color))),
+            border_radius,
+            text: text_ops.map(|text| text.to_text(asset_server)),
+        }
     }
 }
 
-#[derive(Serialize, Deserialize, Default, Debug)]
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
 #[serde(default)]
 struct TextBuilder {
     section_builders: Vec<TextSectionBuilder>,
     justify: JustifyText,
     linebreak_behavior: BreakLineOn,
 }
+#[allow(clippy::wrong_self_convention)]
 impl TextBuilder {
     fn to_text(self, asset_server: AssetServer) -> Text {
         let Self { section_builders, justify, linebreak_behavior } = self;
@@ -159,12 +158,13 @@ impl TextBuilder {
     }
 }
 
-#[derive(Serialize, Deserialize, Default, Debug)]
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
 #[serde(default)]
 struct TextSectionBuilder {
     value: String,
     style_builder: TextStyleBuilder,
 }
+#[allow(clippy::wrong_self_convention)]
 impl TextSectionBuilder {
     fn to_text_section(self, asset_server: AssetServer) -> TextSection {
         let Self { value, style_builder } = self;
@@ -176,7 +176,7 @@ impl TextSectionBuilder {
 }
 
 #[serde_inline_default]
-#[derive(Serialize, Deserialize, Default, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 struct TextStyleBuilder {
     #[serde_inline_default("fonts/FiraSans-Bold.ttf".to_string())]
     font: String,
@@ -185,6 +185,17 @@ struct TextStyleBuilder {
     #[serde_inline_default("BLACK".to_string())]
     color: String,
 }
+// `TextSectionBuilder` falls back to this when `style_builder` is omitted, so it must agree with the serde defaults
+impl Default for TextStyleBuilder {
+    fn default() -> Self {
+        Self {
+            font: "fonts/FiraSans-Bold.ttf".to_string(),
+            font_size: 24.0,
+            color: "BLACK".to_string(),
+        }
+    }
+}
+#[allow(clippy::wrong_self_convention)]
 impl TextStyleBuilder {
     fn to_text_style(self, asset_server: AssetServer) -> TextStyle {
         let Self { font, font_size, color } = self;
@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use serde::Deserialize;
+use std::collections::HashMap;
+use thiserror::Error;
+use crate::g::GameConfigs;
+use crate::builders::GameConfigBuilder;
+use gblpc_core::builders::BuildError;
+pub(super) use gblpc_core::builders::Anchor;
+pub(super) use gblpc_core::builders::ron_options;
+
+pub(super) const DEFAULT_CATALOGUE: &str = "games/default.games.ron";
+
+/// A list of game definitions loaded from a `*.games.ron` file
+#[derive(Asset, TypePath, Deserialize, Debug)]
+#[serde(transparent)]
+pub(super) struct GameCatalogue(pub(super) Vec<GameConfigBuilder>);
+
+#[derive(Resource)]
+pub(super) struct GameCatalogueHandle(pub(super) Handle<GameCatalogue>);
+
+#[derive(Default)]
+pub(super) struct GameCatalogueLoader;
+
+#[derive(Debug, Error)]
+pub(super) enum GameCatalogueLoaderError {
+    #[error("could not read game catalogue: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid game catalogue:\n{0}")]
+    Invalid(GameConfigErrors),
+}
+
+#[derive(Debug, Error)]
+#[error("{file}:{line}:{column}: {kind}")]
+pub(super) struct GameConfigError {
+    pub(super) file: String,
+    pub(super) line: usize,
+    pub(super) column: usize,
+    pub(super) cell: Option<String>,
+    pub(super) kind: GameConfigErrorKind,
+}
+
+#[derive(Debug, Error)]
+pub(super) enum GameConfigErrorKind {
+    #[error("{0}")]
+    Parse(ron::error::Error),
+    #[error("game `{0}` is defined more than once")]
+    DuplicateGame(String),
+    #[error("cell `{cell}` uses unknown color `{color}`")]
+    UnknownColor { cell: String, color: String },
+    /// Caught by the engine's own checks, shared with the command line and the web API
+    #[error(transparent)]
+    Build(BuildError),
+}
+
+#[derive(Debug, Default)]
+pub(super) struct GameConfigErrors(pub(super) Vec<GameConfigError>);
+impl std::fmt::Display for GameConfigErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for error in self.0.iter() {
+            writeln!(f, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Maps schema errors found after deserializing back to a line and column, since RON drops spans once it's parsed
+pub(super) struct SourceLocator<'a> {
+    file: String,
+    source: &'a str,
+}
+impl<'a> SourceLocator<'a> {
+    pub(super) fn new(file: impl Into<String>, source: &'a str) -> Self {
+        Self { file: file.into(), source }
+    }
+
+    /// Finds each anchor after the previous one, stopping at the last one that was found
+    pub(super) fn locate(&self, anchors: &[Anchor]) -> (usize, usize) {
+        let mut offset = 0;
+        for anchor in anchors {
+            match self.find(offset, *anchor) {
+                Some(found) => offset = found,
+                None => break,
+            }
+        }
+        let before = &self.source[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+        (line, column)
+    }
+
+    pub(super) fn error(&self, anchors: &[Anchor], cell: Option<&str>, kind: GameConfigErrorKind) -> GameConfigError {
+        let (line, column) = self.locate(anchors);
+        GameConfigError {
+            file: self.file.clone(),
+            line,
+            column,
+            cell: cell.map(str::to_string),
+            kind,
+        }
+    }
+
+    fn find(&self, from: usize, anchor: Anchor) -> Option<usize> {
+        // Step past the previous match so repeated anchors find the next occurrence
+        let start = if from == 0 { 0 } else { from + 1 };
+        let rest = self.source.get(start..)?;
+        match anchor {
+            Anchor::Key(key) => rest
+                .match_indices(key)
+                .find(|(index, _)| rest[index + key.len()..].trim_start().starts_with(':'))
+                .map(|(index, _)| start + index),
+            Anchor::Quoted(value) => rest.find(&format!("\"{value}\"")).map(|index| start + index),
+            Anchor::Value(key, value) => rest
+                .match_indices(&format!("\"{value}\""))
+                .find(|(index, _)| {
+                    let before = rest[..*index].trim_end();
+                    before.strip_suffix(':').is_some_and(|before| before.trim_end().ends_with(key))
+                })
+                .map(|(index, _)| start + index),
+        }
+    }
+}
+
+/// Parses and validates a whole catalogue, returning every error found
+pub(super) fn parse_catalogue(file: &str, source: &str) -> Result<Vec<GameConfigBuilder>, GameConfigErrors> {
+    let game_config_builders: Vec<GameConfigBuilder> = ron_options()
+        .from_str(source)
+        .map_err(|error| GameConfigErrors(vec![GameConfigError {
+            file: file.to_string(),
+            line: error.position.line,
+            column: error.position.col,
+            cell: None,
+            kind: GameConfigErrorKind::Parse(error.code),
+        }]))?;
+
+    let locator = SourceLocator::new(file, source);
+    let mut errors = Vec::new();
+    let mut names = HashMap::new();
+    for game_config_builder in game_config_builders.iter() {
+        let count = names.entry(game_config_builder.name()).or_insert(0);
+        *count += 1;
+        if *count == 2 {
+            let name = game_config_builder.name();
+            errors.push(locator.error(
+                &[Anchor::Value("name", name), Anchor::Value("name", name)],
+                None,
+                GameConfigErrorKind::DuplicateGame(name.to_string()),
+            ));
+        }
+        errors.extend(game_config_builder.validate(&locator));
+    }
+    if errors.is_empty() {
+        Ok(game_config_builders)
+    } else {
+        Err(GameConfigErrors(errors))
+    }
+}
+
+impl AssetLoader for GameCatalogueLoader {
+    type Asset = GameCatalogue;
+    type Settings = ();
+    type Error = GameCatalogueLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut source = String::new();
+        reader.read_to_string(&mut source).await?;
+        let file = load_context.path().display().to_string();
+        parse_catalogue(&file, &source)
+            .map(GameCatalogue)
+            .map_err(GameCatalogueLoaderError::Invalid)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["games.ron"]
+    }
+}
+
+
+pub(super) fn load_catalogue(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(GameCatalogueHandle(asset_server.load(DEFAULT_CATALOGUE)));
+}
+
+pub(super) fn build_game_configs(
+    mut asset_events: EventReader<AssetEvent<GameCatalogue>>,
+    catalogue_handle: Res<GameCatalogueHandle>,
+    catalogues: Res<Assets<GameCatalogue>>,
+    asset_server: Res<AssetServer>,
+    mut game_configs: ResMut<GameConfigs>,
+) {
+    let id = catalogue_handle.0.id();
+    let changed = asset_events.read().any(|asset_event| {
+        asset_event.is_loaded_with_dependencies(id) || asset_event.is_modified(id) || asset_event.is_removed(id)
+    });
+    if !changed { return }
+
+    // Rebuild from scratch so games renamed or removed in a hot-reloaded catalogue don't linger
+    game_configs.0.clear();
+    for (_, catalogue) in catalogues.iter() {
+        for game_config_builder in catalogue.0.iter().cloned() {
+            match game_config_builder.to_game_config(asset_server.clone()) {
+                Ok(game_config) => { game_configs.0.insert(game_config.name.clone(), game_config); },
+                Err(error) => warn!("Skipped a game the catalogue loader let through: {error}"),
+            }
+        }
+    }
+}
@@ -1,26 +1,49 @@
 use bevy::prelude::*;
-use serde::{Serialize, Deserialize};
-use ron::{self, ser::PrettyConfig, extensions::Extensions, options::Options};
-use bevy::text::BreakLineOn;
-use serde_inline_default::serde_inline_default;
-use std::cmp::Ordering;
+use bevy::ecs::system::SystemId;
+use bevy::window::{AppLifecycle, WindowCloseRequested};
 use std::collections::HashMap;
+use std::time::Duration;
 use bevy_mod_picking::prelude::*;
-use rand::prelude::*;
+use gblpc_core::game::*;
+use gblpc_core::hint::*;
+use gblpc_core::play::{self, Play};
+use gblpc_core::rules::Board;
+use crate::GameState;
 use crate::utils::*;
-use crate::builders::*;
+use crate::catalogue::*;
+use crate::save::*;
 
 pub struct GBLPPlugin;
 impl Plugin for GBLPPlugin {
     fn build(&self, app: &mut App) {
+        let relayout = app.world_mut().register_system(spawn_layout);
         app
+            .insert_resource(Relayout(relayout))
+            .init_asset::<GameCatalogue>()
+            .init_asset_loader::<GameCatalogueLoader>()
             .init_resource::<GameConfigs>()
-            .add_systems(Startup, parse_games);
+            .add_systems(Startup, load_catalogue)
+            .add_systems(Update, (
+                build_game_configs,
+                reload_game_board.run_if(resource_changed::<GameConfigs>),
+            ).chain())
+            .add_plugins(DefaultPickingPlugins)
+            .add_systems(OnEnter(GameState::Playing), spawn_layout)
+            .add_systems(Update, (
+                click_back_button,
+                share_puzzle,
+                ask_hint,
+                (undo_keys, undo_buttons, undo_gestures),
+                (sync_cells, sync_unpaired.after(sync_cells), sync_clues, show_game_state).run_if(resource_exists_and_changed::<GameBoard>),
+                (tick_play_time, autosave).chain(),
+            ).run_if(in_state(GameState::Playing)))
+            .add_systems(OnExit(GameState::Playing), (save_game, despawn_layout).chain())
+            .observe(CellClickLeft::apply_action)
+            .observe(CellClickRight::apply_action)
+            .observe(PuzzleSolved::log);
     }
 }
 
-type NounID = usize;
-
 #[derive(Component)]
 struct Cell {
     x: usize,
@@ -28,95 +51,248 @@ struct Cell {
     model: NounID,
 }
 
-#[derive(Debug)]
-pub(super) enum Noun {
-    SpatialElement(SpatialElement),
+/// How a noun's cells are drawn, kept beside the engine's `Noun` by `NounID`
+#[derive(Clone, Debug, Default)]
+pub(super) struct Look {
+    pub(super) style: Style,
+    pub(super) background_color: BackgroundColor,
+    pub(super) border_color: BorderColor,
+    pub(super) border_radius: BorderRadius,
+    pub(super) text: Option<Text>,
 }
-
-#[derive(Debug)]
-pub(super) enum SpatialElement {
-    Cell {
-        id: NounID,
-        name: String,
-        l_click: Option<Action>,
-        r_click: Option<Action>,
-        style: Style,
-        background_color: BackgroundColor,
-        border_color: BorderColor,
-        border_radius: BorderRadius,
-        text: Option<Text>,
-    },
+impl Look {
+    // Cells are spawned with a single text child, see `spawn_layout`
+    fn paint(&self, commands: &mut Commands, entity: Entity, children: &Children) {
+        commands.entity(entity).insert((self.background_color, self.border_color, self.border_radius));
+        for &child in children.iter() {
+            // The look's `style` lays out the cell's content, so it goes on the text child rather than the grid item
+            commands.entity(child).insert((self.style.clone(), self.text.clone().unwrap_or_default()));
+        }
+    }
 }
 
 #[derive(Resource, Debug, Default)]
 pub(super) struct GameConfigs(pub(super) HashMap<String, GameConfig>);
 
 #[derive(Resource, Debug)]
-struct GameBoard {
+pub(super) struct GameBoard {
     config: GameConfig,
-    grid: Grid,
+    play: Play,
+    /// The node of each cell, `entities[y][x]`
+    entities: Vec<Vec<Entity>>,
+    solved: bool,
+    /// Hints asked for since the game started
+    hints: usize,
+    /// Laid out instead of a generated board when continuing a saved game
+    resume: Option<SavedGame>,
+    /// The puzz.link link the board was opened from, kept with the save
+    link: Option<String>,
 }
+impl GameBoard {
+    pub(super) fn new(config: GameConfig) -> Self {
+        Self {
+            config,
+            play: Play::default(),
+            entities: Vec::new(),
+            solved: false,
+            hints: 0,
+            resume: None,
+            link: None,
+        }
+    }
+
+    pub(super) fn fits(&self, saved: &SavedGame) -> bool {
+        saved.fits(&self.config)
+    }
+
+    pub(super) fn linked(mut self, link: impl Into<String>) -> Self {
+        self.link = Some(link.into());
+        self
+    }
+
+    pub(super) fn resuming(mut self, saved: SavedGame) -> Self {
+        self.resume = Some(saved);
+        self
+    }
+
+    fn save(&self, game_seed: GameSeed, play_time: &PlayTime) -> SavedGame {
+        SavedGame {
+            name: self.config.name.clone(),
+            seed: game_seed.0,
+            revealed: SavedGame::revealed(&self.config, &self.play.cells),
+            cells: self.play.cells.clone(),
+            elapsed: play_time.0,
+            hints: self.hints,
+            history: self.play.history.clone(),
+            link: self.link.clone(),
+        }
+    }
+
+    fn is_solved(&self) -> bool {
+        self.config.is_solved(&self.play)
+    }
+
+    /// Runs the cell's action for the click as one undoable step, firing `PuzzleSolved` when the rules start to hold
+    fn click(&mut self, commands: &mut Commands, game_seed: GameSeed, x: usize, y: usize, click: play::Click) {
+        // A generated no-guess board is drawn again around the first reveal, so that reveal never hits a mine
+        if self.link.is_none() {
+            if let Err(shortfall) = self.config.redraw_for_first_reveal(&mut self.play, game_seed.0, (x, y), click) {
+                warn!("Kept the board of `{}`: {shortfall}", self.config.name);
+            }
+        }
+        if self.play.click(&self.config, x, y, click) {
+            self.check_solved(commands);
+        }
+    }
+
+    fn check_solved(&mut self, commands: &mut Commands) {
+        let solved = self.is_solved();
+        if solved && !self.solved {
+            commands.trigger(PuzzleSolved);
+        }
+        self.solved = solved;
+    }
+
+    fn undo(&mut self, commands: &mut Commands) {
+        if self.play.undo() {
+            self.check_solved(commands);
+        }
+    }
+
+    fn redo(&mut self, commands: &mut Commands) {
+        if self.play.redo() {
+            self.check_solved(commands);
+        }
+    }
+
+    /// Undoes every step since the board last broke no rule
+    fn undo_to_correct(&mut self, commands: &mut Commands) {
+        self.play.undo_to_correct();
+        self.check_solved(commands);
+    }
 
-#[derive(Debug)]
-struct Grid {
-    height: usize,
-    width: usize,
-    cells: Vec<Vec<(usize, Entity)>>,
+    /// A puzz.link link to the board: the one it was opened from, or one made from its givens, regions and clues
+    fn share_link(&self) -> Result<String, String> {
+        if let Some(link) = &self.link {
+            return Ok(link.clone());
+        }
+        let definition = self.config.puzzle_definition(&self.play.cells).map_err(|error| error.to_string())?;
+        definition.to_url().map_err(|error| error.to_string())
+    }
 }
 
-#[derive(Debug, Default)]
-pub(super) struct GameConfig {
-    pub(super) name: String,
-    pub(super) nouns: Nouns,
+/// Seeds every random draw made while building a board, so a seed always rebuilds the same board
+#[derive(Resource, Clone, Copy, Debug)]
+pub(super) struct GameSeed(pub(super) u64);
+impl GameSeed {
+    pub(super) fn random() -> Self {
+        Self(rand::random())
+    }
 }
 
-#[derive(Debug, Default)]
-pub(super) struct Nouns {
-    pub(super) nouns: Vec<Noun>,
-    pub(super) noun_map: HashMap<String, NounID>,
-    pub(super) gen_config: GenConfig,
+/// How long the current game has been played, not counting time spent suspended or after solving it
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub(super) struct PlayTime(pub(super) Duration);
+
+/// The `line`th clue of the game's `track`th track
+#[derive(Component)]
+struct Clue {
+    track: usize,
+    line: usize,
 }
-// impl Nouns {
-//     fn setup(&self, ) {
 
-//     }
-// }
+#[derive(Component)]
+struct BoardRoot;
 
+#[derive(Component)]
+struct BackToMenu;
 
+#[derive(Component)]
+struct AskHint;
+
+#[derive(Component)]
+struct SharePuzzle;
 
-#[derive(Clone, Debug)]
-enum GenMethod {
-    None,
-    Default,
-    Random(f64),
-    // DependOn,
+#[derive(Component)]
+enum UndoButton {
+    Undo,
+    Redo,
+    ToCorrect,
 }
 
-#[derive(Default, Debug)]
-pub(super) struct GenConfig {
-    pub(super) default: NounID,
-    pub(super) probs: Vec<(f64, NounID)>,
+#[derive(Component)]
+struct HintCountText;
+
+#[derive(Component)]
+struct PlayTimeText;
+
+#[derive(Component)]
+struct HintText;
+
+/// Outlines a cell a hint is about, until the board next changes
+#[derive(Component)]
+struct Hinted;
+
+/// A game from the catalogue: the engine's `Game` and how each of its nouns looks
+#[derive(Clone, Debug, Default, Deref, DerefMut)]
+pub(super) struct GameConfig {
+    #[deref]
+    pub(super) game: Game,
+    /// `looks[noun_id]`
+    pub(super) looks: Vec<Look>,
 }
 
-#[derive(Clone, Copy, Debug)]
-pub(super) enum Action {
-    TransformTo(NounID),
+/// Widens the gap on each side facing a cell of another region, so region borders show as thicker lines
+fn region_margin(region_map: &[Vec<Option<usize>>], x: usize, y: usize) -> UiRect {
+    let region = region_map[y][x];
+    let side = |neighbor: Option<&Option<usize>>| match neighbor {
+        Some(&other) if other != region => Val::Px(1.0),
+        _ => Val::ZERO,
+    };
+    UiRect {
+        left: side(x.checked_sub(1).map(|left| &region_map[y][left])),
+        right: side(region_map[y].get(x + 1)),
+        top: side(y.checked_sub(1).map(|top| &region_map[top][x])),
+        bottom: side(region_map.get(y + 1).map(|row| &row[x])),
+    }
 }
 
 fn spawn_layout(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    game_seed: Res<GameSeed>,
     mut game_board: ResMut<GameBoard>,
 ) {
-    // let mut game_board = game_board.into_inner();
-    let width = game_board.grid.width;
-    let height = game_board.grid.height;
+    let width = game_board.config.width;
+    let height = game_board.config.height;
     let font = asset_server.load("fonts/FiraSans-Bold.ttf");
-    commands.spawn(Camera2dBundle::default());
-    
+    let resume = game_board.resume.take().filter(|saved| saved.fits(&game_board.config));
+    let mut play_time = PlayTime::default();
+    let play = match resume {
+        Some(saved) => {
+            info!("Continuing `{}`", saved.name);
+            game_board.hints = saved.hints;
+            play_time.0 = saved.elapsed;
+            Play { cells: saved.cells, history: saved.history }
+        },
+        // A short board still plays, so it's laid out anyway
+        None => Play::new(game_board.config.generate(game_seed.0).unwrap_or_else(|shortfall| {
+            warn!("Generating `{}`: {shortfall}", game_board.config.name);
+            shortfall.board
+        })),
+    };
+    game_board.config.fill_tracks(&play.start());
+    let tracks = &game_board.config.tracks;
+    let on_side = |side: Side| tracks.iter().filter(|track| track.side == side).count();
+    let (top, left) = (on_side(Side::Top), on_side(Side::Left));
+    let columns = left + width + on_side(Side::Right);
+    let rows = top + height + on_side(Side::Bottom);
+    let mut entities = Vec::new();
+    commands.insert_resource(play_time);
+
     // Top-level grid (app frame)
     commands
-        .spawn(NodeBundle {
+        .spawn((BoardRoot, NodeBundle {
             style: Style {
                 // Use the CSS Grid algorithm for laying out this node
                 display: Display::Grid,
@@ -140,7 +316,7 @@ fn spawn_layout(
             },
             background_color: BackgroundColor(Color::WHITE),
             ..default()
-        })
+        }))
         .with_children(|builder| {
             spawn_header(builder, &font, &game_board.config.name);
             
@@ -152,17 +328,17 @@ fn spawn_layout(
                         height: Val::Percent(100.0),
                         // Make the grid have a 1:1 aspect ratio meaning it will scale as an exact square
                         // As the height is set explicitly, this means the width will adjust to match the height
-                        aspect_ratio: Some(1.0),
+                        aspect_ratio: Some(columns as f32 / rows as f32),
                         // Use grid layout for this node
                         display: Display::Grid,
                         // Add 24px of padding around the grid
                         padding: UiRect::all(Val::Px(24.0)),
                         // Set the grid to have 4 columns all with sizes minmax(0, 1fr)
                         // This creates 4 exactly evenly sized columns
-                        grid_template_columns: RepeatedGridTrack::flex(width as u16, 1.0),
+                        grid_template_columns: RepeatedGridTrack::flex(columns as u16, 1.0),
                         // Set the grid to have 4 rows all with sizes minmax(0, 1fr)
                         // This creates 4 exactly evenly sized rows
-                        grid_template_rows: RepeatedGridTrack::flex(height as u16, 1.0),
+                        grid_template_rows: RepeatedGridTrack::flex(rows as u16, 1.0),
                         // Set a 12px gap/gutter between rows and columns
                         row_gap: Val::Px(1.0),
                         column_gap: Val::Px(1.0),
@@ -177,20 +353,12 @@ fn spawn_layout(
                     // grid cell. The order in which this is performed can be controlled using the grid_auto_flow
                     // style property.
 
-                    let mut rng = rand::thread_rng();
-                    let probs = &game_board.config.nouns.gen_config.probs;
-                    let nouns = &game_board.config.nouns.nouns;
-                    let mut cells = Vec::new();
-                    for y in 0..height {
+                    let looks = &game_board.config.looks;
+                    let region_map = game_board.config.region_map();
+                    for (y, generated_row) in play.cells.iter().enumerate() {
                         let mut row = Vec::new();
-                        for x in 0..width {
-                            let r: f64 = rng.gen();
-                            let mut gen_id = game_board.config.nouns.gen_config.default;
-                            for (prob, noun_id) in probs.iter() {
-                                if r > *prob { continue }
-                                gen_id = *noun_id;
-                                break;
-                            }
+                        for (x, &gen_id) in generated_row.iter().enumerate() {
+                            let Look { style, background_color, border_color, border_radius, text } = looks[gen_id].clone();
                             let entity_id = builder
                                 .spawn((
                                     PickableBundle::default(),
@@ -198,43 +366,126 @@ fn spawn_layout(
                                         style: Style {
                                             display: Display::Grid,
                                             padding: UiRect::all(Val::Px(3.0)),
+                                            margin: region_margin(&region_map, x, y),
+                                            // Placed explicitly so the tracks can take the rows and columns around the board
+                                            grid_column: GridPlacement::start((left + x + 1) as i16),
+                                            grid_row: GridPlacement::start((top + y + 1) as i16),
                                             ..default()
                                         },
-                                        background_color: BackgroundColor(Color::srgb(0.75, 0.75, 0.75)),
+                                        background_color,
+                                        border_color,
+                                        border_radius,
                                         ..default()
                                     },
                                     Cell { x, y, model: gen_id },
                                 ))
-                                // .with_children(|builder| {
-                                //     builder.spawn(TextBundle::from_section(
-                                //         " ",
-                                //         TextStyle {
-                                //             font: font.clone(),
-                                //             font_size: 48.0,
-                                //             color: BLACK,
-                                //         },
-                                //     ));
-                                // })
+                                .with_children(|builder| {
+                                    builder.spawn(TextBundle {
+                                        style,
+                                        text: text.unwrap_or_default(),
+                                        ..default()
+                                    });
+                                })
                                 .id();
-                            row.push((gen_id, entity_id));
+                            row.push(entity_id);
                         }
-                        cells.push(row);
+                        entities.push(row);
                     }
-                    for row in cells {
-                        game_board.grid.cells.push(row);
+
+                    for (index, track) in tracks.iter().enumerate() {
+                        // Tracks on the same side line up outwards in the order they're listed
+                        let nth = tracks[..index].iter().filter(|other| other.side == track.side).count();
+                        let away = match track.side {
+                            Side::Top => top - nth,
+                            Side::Left => left - nth,
+                            Side::Bottom => top + height + nth + 1,
+                            Side::Right => left + width + nth + 1,
+                        };
+                        for (line, clue) in track.clues.iter().enumerate() {
+                            let along = line + 1 + if track.side.counts_columns() { left } else { top };
+                            let (column, row) = if track.side.counts_columns() { (along, away) } else { (away, along) };
+                            builder
+                                .spawn((
+                                    NodeBundle {
+                                        style: Style {
+                                            display: Display::Grid,
+                                            justify_items: JustifyItems::Center,
+                                            align_items: AlignItems::Center,
+                                            grid_column: GridPlacement::start(column as i16),
+                                            grid_row: GridPlacement::start(row as i16),
+                                            ..default()
+                                        },
+                                        background_color: BackgroundColor(Color::WHITE),
+                                        ..default()
+                                    },
+                                    Clue { track: index, line },
+                                ))
+                                .with_children(|builder| {
+                                    builder.spawn(TextBundle::from_section(
+                                        clue.map_or_else(String::new, |clue| clue.to_string()),
+                                        TextStyle {
+                                            font: font.clone(),
+                                            font_size: 24.0,
+                                            color: CLUE_COLOR,
+                                        },
+                                    ));
+                                });
+                        }
                     }
                 });
 
-            spawn_right_side_bar(builder, &font);
+            spawn_right_side_bar(builder, &font, |builder| {
+                builder.spawn(TextBundle::from_section(
+                    format!("Seed : {}", game_seed.0),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 16.0,
+                        ..default()
+                    },
+                ));
+                builder.spawn((PlayTimeText, TextBundle::from_section(
+                    format_play_time(play_time.0),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 16.0,
+                        ..default()
+                    },
+                )));
+                builder.spawn((HintCountText, TextBundle::from_section(
+                    format!("Hints : {}", game_board.hints),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 16.0,
+                        ..default()
+                    },
+                )));
+                spawn_button(builder, &font, "Hint (H)", AskHint);
+                builder.spawn((HintText, TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 14.0,
+                        ..default()
+                    },
+                ).with_style(Style {
+                    max_width: Val::Px(180.0),
+                    ..default()
+                })));
+                spawn_button(builder, &font, "Undo (Ctrl+Z)", UndoButton::Undo);
+                spawn_button(builder, &font, "Redo (Ctrl+Y)", UndoButton::Redo);
+                spawn_button(builder, &font, "Undo to last correct", UndoButton::ToCorrect);
+                spawn_button(builder, &font, "Share link", SharePuzzle);
+                spawn_button(builder, &font, "Back", BackToMenu);
+            });
             spawn_footer(builder);
         });
+    game_board.play = play;
+    game_board.entities = entities;
+    game_board.solved = game_board.is_solved();
 
     for y in 0..height {
         for x in 0..width {
-            let (noun_id, entity) = game_board.grid.cells[y][x];
-            if let Some(cell) = game_board.config.nouns.nouns.get(noun_id) {
-
-            }
+            let entity = game_board.entities[y][x];
             if let Some(mut entity_commands) = commands.get_entity(entity) {
                 entity_commands.insert(
                     On::<Pointer<Click>>::commands_mut(move |click, commands| {
@@ -254,17 +505,79 @@ fn spawn_layout(
     }
 }
 
+fn despawn_layout(
+    mut commands: Commands,
+    board_roots: Query<Entity, With<BoardRoot>>,
+) {
+    for entity in board_roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<GameBoard>();
+}
+
+fn click_back_button(
+    mut next_state: ResMut<NextState<GameState>>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<BackToMenu>)>,
+) {
+    if interaction_query.iter().any(|interaction| *interaction == Interaction::Pressed) {
+        next_state.set(GameState::Menu);
+    }
+}
+
+// Swaps in the rebuilt config for the running game, keeping every cell and step whose noun still exists by name, or
+// lays the game out afresh once its board changed shape
+fn reload_game_board(
+    mut commands: Commands,
+    game_configs: Res<GameConfigs>,
+    game_board: Option<ResMut<GameBoard>>,
+    relayout: Res<Relayout>,
+    board_roots: Query<Entity, With<BoardRoot>>,
+    mut cell_query: Query<(&mut Cell, &Children)>,
+) {
+    let Some(mut game_board) = game_board else { return };
+    let Some(config) = game_configs.0.get(&game_board.config.name) else { return };
+    let mut config = config.clone();
+    let Some(play) = game_board.play.carried_over(&game_board.config, &config) else {
+        info!("Laying out `{}` again, as its board changed shape", config.name);
+        for entity in board_roots.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        *game_board = GameBoard::new(config);
+        commands.run_system(relayout.0);
+        return;
+    };
+    for (&noun_id, entity) in play.cells.iter().flatten().zip(game_board.entities.iter().flatten()) {
+        if let Ok((mut cell, children)) = cell_query.get_mut(*entity) {
+            cell.model = noun_id;
+            config.looks[noun_id].paint(&mut commands, *entity, children);
+        }
+    }
+    // Clues are counted on the board the game started from
+    config.fill_tracks(&play.start());
+    info!("Reloaded game `{}`", config.name);
+    game_board.play = play;
+    game_board.config = config;
+    game_board.solved = game_board.is_solved();
+}
+
+/// `spawn_layout` as a system of its own, to lay out a running game again
+#[derive(Resource)]
+struct Relayout(SystemId);
+
 #[derive(Event)]
 struct CellClickLeft {
     x: usize,
     y: usize,
 }
 impl CellClickLeft {
-    fn reveal_cell(
+    fn apply_action(
         trigger: Trigger<Self>,
-        game_board: ResMut<GameBoard>,
+        mut commands: Commands,
+        game_seed: Res<GameSeed>,
+        mut game_board: ResMut<GameBoard>,
     ) {
-        let Self { x, y } = trigger.event();
+        let &Self { x, y } = trigger.event();
+        game_board.click(&mut commands, *game_seed, x, y, play::Click::Left);
     }
 }
 
@@ -273,13 +586,288 @@ struct CellClickRight {
     x: usize,
     y: usize,
 }
+impl CellClickRight {
+    fn apply_action(
+        trigger: Trigger<Self>,
+        mut commands: Commands,
+        game_seed: Res<GameSeed>,
+        mut game_board: ResMut<GameBoard>,
+    ) {
+        let &Self { x, y } = trigger.event();
+        game_board.click(&mut commands, *game_seed, x, y, play::Click::Right);
+    }
+}
 
-// fn detect_change(query: Query<(Entity, &mut NodeBundle, Cell), Changed<Cell>>) {
+// Keeps every `Cell` and its node in step with the board's cells after actions change it, which also retires any hint shown
+fn sync_cells(
+    mut commands: Commands,
+    game_board: Res<GameBoard>,
+    mut cell_query: Query<(Entity, &mut Cell, &Children)>,
+    hinted_query: Query<Entity, With<Hinted>>,
+    mut hint_text_query: Query<&mut Text, With<HintText>>,
+) {
+    let mut changed = false;
+    for (entity, mut cell, children) in cell_query.iter_mut() {
+        let Some(&noun_id) = game_board.play.cells.get(cell.y).and_then(|row| row.get(cell.x)) else { continue };
+        if cell.model == noun_id { continue }
+        cell.model = noun_id;
+        game_board.config.looks[noun_id].paint(&mut commands, entity, children);
+        changed = true;
+    }
+    if !changed { return }
+    for entity in hinted_query.iter() {
+        commands.entity(entity).remove::<(Hinted, Outline)>();
+    }
+    for mut text in hint_text_query.iter_mut() {
+        text.sections[0].value.clear();
+    }
+}
 
-// }
+const UNPAIRED_COLOR: Color = Color::srgb(0.9, 0.1, 0.1);
+
+// Reddens the border of each cell a `Paired` rule leaves without a partner, e.g. a tent no tree is left for
+fn sync_unpaired(
+    mut commands: Commands,
+    game_board: Res<GameBoard>,
+    cell_query: Query<(Entity, &Cell)>,
+) {
+    let unpaired = game_board.config.unpaired(&game_board.play);
+    for (entity, cell) in cell_query.iter() {
+        let border_color = if unpaired.contains(&(cell.x, cell.y)) {
+            BorderColor(UNPAIRED_COLOR)
+        } else {
+            game_board.config.looks[cell.model].border_color
+        };
+        commands.entity(entity).insert(border_color);
+    }
+}
 
-fn merge_node_bundle() {
+const CLUE_COLOR: Color = Color::BLACK;
+const CLUE_SATISFIED_COLOR: Color = Color::srgb(0.7, 0.7, 0.7);
+const CLUE_OVER_COLOR: Color = Color::srgb(0.9, 0.1, 0.1);
 
+// Greys out each clue its row or column meets and reddens each one it goes over
+fn sync_clues(
+    game_board: Res<GameBoard>,
+    clue_query: Query<(&Clue, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    let config = &game_board.config;
+    for (clue, children) in clue_query.iter() {
+        let Some(track) = config.tracks.get(clue.track) else { continue };
+        let Some(&Some(number)) = track.clues.get(clue.line) else { continue };
+        let count = game_board.play.count_in(&track.side.line(clue.line, config.width, config.height), &track.nouns);
+        let color = match count.cmp(&number) {
+            std::cmp::Ordering::Less => CLUE_COLOR,
+            std::cmp::Ordering::Equal => CLUE_SATISFIED_COLOR,
+            std::cmp::Ordering::Greater => CLUE_OVER_COLOR,
+        };
+        for &child in children.iter() {
+            let Ok(mut text) = text_query.get_mut(child) else { continue };
+            text.sections[0].style.color = color;
+        }
+    }
+}
+
+fn undo_keys(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut game_board: ResMut<GameBoard>,
+) {
+    if !keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight, KeyCode::SuperLeft, KeyCode::SuperRight]) { return }
+    let shift = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if keyboard.just_pressed(KeyCode::KeyY) || (shift && keyboard.just_pressed(KeyCode::KeyZ)) {
+        game_board.redo(&mut commands);
+    } else if keyboard.just_pressed(KeyCode::KeyZ) {
+        game_board.undo(&mut commands);
+    }
+}
+
+fn undo_buttons(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &UndoButton), Changed<Interaction>>,
+    mut game_board: ResMut<GameBoard>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed { continue }
+        match button {
+            UndoButton::Undo => game_board.undo(&mut commands),
+            UndoButton::Redo => game_board.redo(&mut commands),
+            UndoButton::ToCorrect => game_board.undo_to_correct(&mut commands),
+        }
+    }
+}
+
+// A two-finger swipe left undoes and right redoes, far enough that lifting the fingers doesn't click a cell
+fn undo_gestures(
+    mut commands: Commands,
+    touches: Res<Touches>,
+    mut swipe: Local<Option<Vec2>>,
+    mut game_board: ResMut<GameBoard>,
+) {
+    const SWIPE: f32 = 80.0;
+    let fingers = touches.iter().collect::<Vec<_>>();
+    if let [a, b] = fingers[..] {
+        let distance = (a.distance() + b.distance()) / 2.0;
+        *swipe = Some(swipe.map_or(distance, |longest| if distance.length() > longest.length() { distance } else { longest }));
+        return;
+    }
+    if !fingers.is_empty() { return }
+    let Some(distance) = swipe.take() else { return };
+    if distance.x.abs() < SWIPE || distance.x.abs() < distance.y.abs() { return }
+    if distance.x < 0.0 {
+        game_board.undo(&mut commands);
+    } else {
+        game_board.redo(&mut commands);
+    }
+}
+
+/// Where shared links are copied, kept open since an X11 clipboard only lasts as long as whoever set it
+#[derive(Default)]
+struct Clipboard(
+    #[cfg(not(any(target_arch = "wasm32", target_os = "android", target_os = "ios")))]
+    Option<arboard::Clipboard>,
+);
+impl Clipboard {
+    #[cfg(not(any(target_arch = "wasm32", target_os = "android", target_os = "ios")))]
+    fn copy(&mut self, text: &str) -> bool {
+        if self.0.is_none() {
+            self.0 = arboard::Clipboard::new().map_err(|error| warn!("No clipboard {error:?}")).ok();
+        }
+        let Some(clipboard) = self.0.as_mut() else { return false };
+        clipboard.set_text(text).map_err(|error| warn!("Failed to copy {error:?}")).is_ok()
+    }
+
+    #[cfg(any(target_arch = "wasm32", target_os = "android", target_os = "ios"))]
+    fn copy(&mut self, _text: &str) -> bool {
+        false
+    }
+}
+
+// Copies a link to the puzzle where there's a clipboard, and shows it where the hints go either way
+fn share_puzzle(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<SharePuzzle>)>,
+    game_board: Res<GameBoard>,
+    mut clipboard: Local<Clipboard>,
+    mut hint_text_query: Query<&mut Text, With<HintText>>,
+) {
+    if !interaction_query.iter().any(|interaction| *interaction == Interaction::Pressed) { return }
+    let message = match game_board.share_link() {
+        Ok(link) => {
+            info!("Share link {link}");
+            if clipboard.copy(&link) { format!("Copied {link}") } else { link }
+        },
+        Err(error) => format!("Can't share: {error}"),
+    };
+    for mut text in hint_text_query.iter_mut() {
+        text.sections[0].value.clone_from(&message);
+    }
+}
+
+fn ask_hint(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<AskHint>)>,
+    mut game_board: ResMut<GameBoard>,
+    hinted_query: Query<Entity, With<Hinted>>,
+    mut hint_text_query: Query<&mut Text, (With<HintText>, Without<HintCountText>)>,
+    mut hint_count_query: Query<&mut Text, (With<HintCountText>, Without<HintText>)>,
+) {
+    let pressed = interaction_query.iter().any(|interaction| *interaction == Interaction::Pressed);
+    if !pressed && !keyboard.just_pressed(KeyCode::KeyH) { return }
+
+    for entity in hinted_query.iter() {
+        commands.entity(entity).remove::<(Hinted, Outline)>();
+    }
+    let hint = game_board.config.hint(&game_board.play);
+    let explanation = match &hint {
+        Some(hint) => hint.explanation.clone(),
+        None => "No hint: nothing follows from the rules yet".to_string(),
+    };
+    if let Some(Hint { cell, because, .. }) = hint {
+        game_board.hints += 1;
+        let outlined = because.into_iter()
+            .map(|cell| (cell, Color::srgb(0.1, 0.5, 1.0)))
+            .chain(cell.map(|(cell, _)| (cell, Color::srgb(1.0, 0.8, 0.0))));
+        for ((x, y), color) in outlined {
+            let entity = game_board.entities[y][x];
+            commands.entity(entity).insert((Hinted, Outline::new(Val::Px(3.0), Val::ZERO, color)));
+        }
+    }
+    for mut text in hint_text_query.iter_mut() {
+        text.sections[0].value.clone_from(&explanation);
+    }
+    for mut text in hint_count_query.iter_mut() {
+        text.sections[0].value = format!("Hints : {}", game_board.hints);
+    }
+}
+
+#[derive(Event)]
+pub(super) struct PuzzleSolved;
+impl PuzzleSolved {
+    fn log(
+        _trigger: Trigger<Self>,
+        game_board: Res<GameBoard>,
+    ) {
+        info!("Solved `{}`", game_board.config.name);
+    }
+}
+
+fn show_game_state(
+    game_board: Res<GameBoard>,
+    mut text_query: Query<&mut Text, With<GameStateText>>,
+) {
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = if game_board.solved { "Solved!" } else { "Playing" }.to_string();
+    }
+}
+
+fn format_play_time(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    format!("Time : {}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn tick_play_time(
+    time: Res<Time>,
+    game_board: Res<GameBoard>,
+    mut play_time: ResMut<PlayTime>,
+    mut text_query: Query<&mut Text, With<PlayTimeText>>,
+) {
+    if game_board.solved { return }
+    let shown = play_time.0.as_secs();
+    play_time.0 += time.delta();
+    if play_time.0.as_secs() == shown { return }
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format_play_time(play_time.0);
+    }
+}
+
+// Saves after every move, and when the app is about to be suspended or closed so the clock is kept too
+fn autosave(
+    mut lifecycle_events: EventReader<AppLifecycle>,
+    mut close_events: EventReader<WindowCloseRequested>,
+    game_board: Res<GameBoard>,
+    game_seed: Res<GameSeed>,
+    play_time: Res<PlayTime>,
+) {
+    let suspending = lifecycle_events.read().filter(|event| matches!(event, AppLifecycle::WillSuspend | AppLifecycle::Suspended)).count() > 0;
+    let closing = close_events.read().count() > 0;
+    if suspending || closing || game_board.is_changed() {
+        save_game(game_board, game_seed, play_time);
+    }
+}
+
+// A solved game has nothing left to continue
+fn save_game(
+    game_board: Res<GameBoard>,
+    game_seed: Res<GameSeed>,
+    play_time: Res<PlayTime>,
+) {
+    if game_board.solved {
+        SavedGame::clear();
+    } else {
+        game_board.save(*game_seed, &play_time).store();
+    }
 }
 
 // pub fn rr(asset_server: Res<AssetServer>) {
@@ -358,11 +946,4 @@ fn merge_node_bundle() {
 // }
 
 
-// https://github.com/ron-rs/ron/issues/115
-
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, States)]
-enum GameState {
-    #[default]
-    MainMenu,
-    // InGame(String),
-}
\ No newline at end of file
+// https://github.com/ron-rs/ron/issues/115
\ No newline at end of file
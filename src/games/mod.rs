@@ -1,9 +1,9 @@
+pub mod puzzle;
+
 use bevy::prelude::*;
 use bevy_mod_picking::prelude::*;
-use rand::Rng;
 use std::fmt::Debug;
-use bevy::color::palettes::css::*;
-use web_sys::console;
+use gblpc_core::no_guess;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 struct UVec2<T>
@@ -46,10 +46,7 @@ impl MSCell {
         }
     }
     fn is_mine(&self) -> bool {
-        match self.cell_state {
-            MSCellState::Mine => true,
-            _ => false,
-        }
+        matches!(self.cell_state, MSCellState::Mine)
     }
 }
 
@@ -61,7 +58,7 @@ pub enum MSGameState {
 }
 impl MSGameState {
     fn is_game_over(&self) -> bool {
-        if let Self::GameOver = self { true } else { false }
+        matches!(self, Self::GameOver)
     }
 }
 
@@ -71,6 +68,8 @@ pub struct MSGameBoard {
     pub is_mine_spawned: bool,
     pub grid_size: usize,
     pub mine_count: usize,
+    /// Redraws mines up to this many times until the board can be cleared without guessing, or only spares the clicked cell if `None`
+    pub no_guess_attempts: Option<usize>,
 }
 impl MSGameBoard {
     fn new(grid_size: usize, mine_count: usize) -> Self {
@@ -80,6 +79,13 @@ impl MSGameBoard {
             ..default()
         }
     }
+
+    fn with_no_guess(self, max_attempts: usize) -> Self {
+        Self {
+            no_guess_attempts: Some(max_attempts),
+            ..self
+        }
+    }
 }
 
 #[derive(Event)]
@@ -98,7 +104,7 @@ impl Plugin for MineSweeperPlugin {
         app
             .add_plugins(DefaultPickingPlugins)
             .insert_resource(MSGameState::Progressing)
-            .insert_resource(MSGameBoard::new(10, 15))
+            .insert_resource(MSGameBoard::new(10, 15).with_no_guess(1000))
             .add_systems(Startup, spawn_layout)
             .observe(check_win_condition)
             .observe(reveal_cell);
@@ -107,22 +113,34 @@ impl Plugin for MineSweeperPlugin {
 
 fn spawn_mines(
     init_pos: UVec2<usize>,
-    mut cell_query: &mut Query<(&mut MSCell, &mut BackgroundColor)>,
-    game_board: Res<MSGameBoard>,
+    cell_query: &mut Query<(&mut MSCell, &mut BackgroundColor)>,
+    game_board: &MSGameBoard,
 ) {
     let mut rng = rand::thread_rng();
     let grid_size = game_board.grid_size;
     let mine_count = game_board.mine_count;
+    let start = (init_pos.x, init_pos.y);
 
-    let mut spawned_mines = 0;
-    while spawned_mines < mine_count {
-        let [x, y] = [0; 2].map(|_| rng.gen_range(0..grid_size));
-        if x == init_pos.x && y == init_pos.y { continue }
-        if let Some(&cell_entity) = game_board.cells.get(y).and_then(|row| row.get(x)) {
-            if let Ok((mut cell, _)) = cell_query.get_mut(cell_entity) {
-                if let MSCellState::Mine = cell.cell_state { continue }
+    let mines = match game_board.no_guess_attempts {
+        Some(max_attempts) => no_guess::generate(grid_size, grid_size, mine_count, start, max_attempts, &mut rng).map(|(mines, report)| {
+            if report.solvable {
+                info!("no-guess board found after {} attempts in {:?}", report.attempts, report.elapsed);
+            } else {
+                warn!("no no-guess board in {} attempts ({:?}), this one may need a guess", report.attempts, report.elapsed);
+            }
+            mines
+        }),
+        None => no_guess::place_mines(grid_size, grid_size, mine_count, &[start], &mut rng),
+    };
+    let mines = mines.unwrap_or_else(|error| {
+        warn!("no mines placed: {error}");
+        vec![vec![false; grid_size]; grid_size]
+    });
+    for (y, row) in mines.iter().enumerate() {
+        for (x, &mine) in row.iter().enumerate() {
+            if !mine { continue }
+            if let Ok((mut cell, _)) = cell_query.get_mut(game_board.cells[y][x]) {
                 cell.cell_state = MSCellState::Mine;
-                spawned_mines += 1;
             }
         }
     }
@@ -387,11 +405,11 @@ fn reveal_cell(
 ) {
     let UVec2 { x, y } = trigger.event().pos;
     let triggered_entity = game_board.cells[y][x];
-    let (mut cell, mut background_color) = cell_query.get_mut(triggered_entity).unwrap();
     if !game_board.is_mine_spawned {
         game_board.is_mine_spawned = true;
-        return spawn_mines(UVec2::new(x, y), &mut cell_query, game_board.into());
+        spawn_mines(UVec2::new(x, y), &mut cell_query, &game_board);
     }
+    let (mut cell, mut background_color) = cell_query.get_mut(triggered_entity).unwrap();
 
     if cell.is_revealed { return }
     cell.is_revealed = true;
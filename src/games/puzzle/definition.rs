@@ -0,0 +1,21 @@
+use bevy::prelude::*;
+use std::marker::PhantomData;
+
+use super::cell::Cell;
+use super::grid::PuzzleGrid;
+pub use gblpc_core::puzzle::definition::*;
+
+pub struct GridPlugin<C: Cell + Component + Default> {
+    _phantom: PhantomData<C>,
+}
+
+impl<C: Cell + Component + Default> Plugin for GridPlugin<C> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PuzzleGrid<C>>()
+           .add_systems(Update, update_grid::<C>);
+    }
+}
+
+fn update_grid<C: Cell + Component + Default>(_grid: ResMut<PuzzleGrid<C>>) {
+    // Implementation of grid update logic...
+}
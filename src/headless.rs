@@ -0,0 +1,356 @@
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use std::collections::HashMap;
+use std::path::Path;
+use thiserror::Error;
+use crate::builders::GameConfigBuilder;
+use crate::catalogue::{parse_catalogue, ron_options};
+use gblpc_core::game::{Game, NounID};
+use gblpc_core::generate::Shortfall;
+use gblpc_core::link::LinkError;
+use gblpc_core::puzzle::difficulty::Difficulty;
+
+#[derive(Debug, Error)]
+pub enum HeadlessError {
+    #[error("could not read `{0}`: {1}")]
+    Io(String, std::io::Error),
+    #[error("{0}")]
+    Catalogue(String),
+    #[error("{0}")]
+    Format(String),
+    #[error("no game named `{0}`")]
+    UnknownGame(String),
+    #[error("`{game}` has no cell named `{noun}`")]
+    UnknownNoun { game: String, noun: String },
+    #[error("board rows must be non-empty and all the same length")]
+    RaggedBoard,
+    #[error("board is {}x{}, but `{game}` is played on {}x{}", found.0, found.1, expected.0, expected.1)]
+    BoardSize { game: String, expected: (usize, usize), found: (usize, usize) },
+    #[error("`{game}` with seed {seed}: {source}")]
+    Shortfall { game: String, seed: u64, source: Shortfall },
+    #[error("`{game}` can't be rated as a puzz.link puzzle: {source}")]
+    Link { game: String, source: LinkError },
+}
+
+/// What the command line reads and writes, picked by file extension
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Ron,
+    Json,
+}
+impl Format {
+    /// `.json` files are JSON, anything else RON
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Self::Json,
+            _ => Self::Ron,
+        }
+    }
+
+    pub fn write(&self, value: &impl Serialize) -> Result<String, HeadlessError> {
+        match self {
+            Self::Ron => ron_options().to_string_pretty(value, ron::ser::PrettyConfig::default()).map_err(|error| HeadlessError::Format(error.to_string())),
+            Self::Json => serde_json::to_string_pretty(value).map_err(|error| HeadlessError::Format(error.to_string())),
+        }
+    }
+
+    pub fn read<T: DeserializeOwned>(&self, source: &str) -> Result<T, HeadlessError> {
+        match self {
+            Self::Ron => ron_options().from_str(source).map_err(|error| HeadlessError::Format(error.to_string())),
+            Self::Json => serde_json::from_str(source).map_err(|error| HeadlessError::Format(error.to_string())),
+        }
+    }
+}
+
+/// A board by noun names, `cells[y][x]`, so it survives a catalogue that reorders its cells
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoardFile {
+    pub game: String,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub cells: Vec<Vec<String>>,
+}
+
+/// How far hints alone get on a board
+#[derive(Clone, Debug, Serialize)]
+pub struct Solve {
+    pub board: BoardFile,
+    pub solved: bool,
+    /// Each hint applied, in order
+    pub steps: Vec<String>,
+    /// Why solving stopped short, if it did
+    pub stuck: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Rate {
+    pub game: String,
+    pub solved: bool,
+    pub steps: usize,
+    pub difficulty: Difficulty,
+}
+
+/// Every game in the catalogues given, checked as `check` and the app check them, then built by `gblpc-core` alone
+pub struct Games(HashMap<String, Game>);
+impl Games {
+    /// Reads every catalogue, later ones replacing games of the same name, and reports every error across them
+    pub fn load(paths: &[impl AsRef<Path>]) -> Result<Self, HeadlessError> {
+        let mut errors = String::new();
+        let mut games = HashMap::new();
+        for path in paths.iter().map(AsRef::as_ref) {
+            match read_catalogue(path) {
+                Ok(game_config_builders) => {
+                    for game_config_builder in game_config_builders {
+                        let game = game_config_builder.game_builder().to_game()
+                            .map_err(|error| HeadlessError::Catalogue(error.to_string()))?;
+                        games.insert(game.name.clone(), game);
+                    }
+                },
+                Err(HeadlessError::Catalogue(catalogue_errors)) => errors.push_str(&format!("{catalogue_errors}\n")),
+                Err(error) => return Err(error),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(HeadlessError::Catalogue(errors.trim_end().to_string()));
+        }
+        Ok(Self(games))
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self.0.keys().map(String::as_str).collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    fn config(&self, game: &str) -> Result<&Game, HeadlessError> {
+        self.0.get(game).ok_or_else(|| HeadlessError::UnknownGame(game.to_string()))
+    }
+
+    /// `count` boards from consecutive seeds starting at `seed`, failing on the first that can't place every noun asked for
+    pub fn generate(&self, game: &str, seed: u64, count: usize) -> Result<Vec<BoardFile>, HeadlessError> {
+        let config = self.config(game)?;
+        (0..count as u64)
+            .map(|offset| {
+                let seed = seed.wrapping_add(offset);
+                let cells = config.generate(seed)
+                    .map_err(|source| HeadlessError::Shortfall { game: game.to_string(), seed, source })?;
+                Ok(BoardFile { game: game.to_string(), seed: Some(seed), cells: names(config, &cells) })
+            })
+            .collect()
+    }
+
+    /// Applies hints until the rules hold or none follows
+    pub fn solve(&self, board: &BoardFile) -> Result<Solve, HeadlessError> {
+        let (solve, _) = self.solve_counting(board)?;
+        Ok(solve)
+    }
+
+    /// Rates a board as `PuzzleDefinition::rate` rates the puzzle its `puzz_link` makes of it, whatever the game is called.
+    /// Games without one, or whose puzzle type has no technique solver, are rated by their hardest hint through
+    /// `Difficulty::of_places`, and fiendish when hints can't finish.
+    pub fn rate(&self, board: &BoardFile) -> Result<Rate, HeadlessError> {
+        let mut config = self.config(&board.game)?.clone();
+        let cells = noun_ids(&config, board)?;
+        config.fill_tracks(&cells);
+        if config.puzz_link.is_some() {
+            let definition = config.puzzle_definition(&cells)
+                .map_err(|source| HeadlessError::Link { game: board.game.clone(), source })?;
+            if let Some(rating) = definition.rate() {
+                let steps = rating.techniques.iter().map(|&(_, count)| count).sum();
+                return Ok(Rate { game: board.game.clone(), solved: rating.solved, steps, difficulty: rating.difficulty() });
+            }
+        }
+        let (solve, places) = self.solve_counting(board)?;
+        let difficulty = if solve.solved { Difficulty::of_places(places) } else { Difficulty::Fiendish };
+        Ok(Rate { game: board.game.clone(), solved: solve.solved, steps: solve.steps.len(), difficulty })
+    }
+
+    // Also returns the most places any one hint needed
+    fn solve_counting(&self, board: &BoardFile) -> Result<(Solve, usize), HeadlessError> {
+        let mut config = self.config(&board.game)?.clone();
+        let mut cells = noun_ids(&config, board)?;
+        config.fill_tracks(&cells);
+        let mut steps = Vec::new();
+        let mut most_places = 0;
+        let stuck = loop {
+            if config.is_solved(&cells) { break None }
+            let Some(hint) = config.hint(&cells) else { break Some("No hint: nothing follows from the rules yet".to_string()) };
+            let Some(((x, y), noun_id)) = hint.cell else { break Some(hint.explanation) };
+            cells[y][x] = noun_id;
+            most_places = most_places.max(hint.places);
+            steps.push(hint.explanation);
+        };
+        let solve = Solve {
+            board: BoardFile { cells: names(&config, &cells), ..board.clone() },
+            solved: stuck.is_none(),
+            steps,
+            stuck,
+        };
+        Ok((solve, most_places))
+    }
+}
+
+/// Parses and validates a catalogue, returning how many games it defines or every error in it
+pub fn check(path: &Path) -> Result<usize, HeadlessError> {
+    read_catalogue(path).map(|game_config_builders| game_config_builders.len())
+}
+
+// The one validator every command goes through, the same the app's catalogue loader uses
+fn read_catalogue(path: &Path) -> Result<Vec<GameConfigBuilder>, HeadlessError> {
+    let file = path.display().to_string();
+    let source = std::fs::read_to_string(path).map_err(|error| HeadlessError::Io(file.clone(), error))?;
+    parse_catalogue(&file, &source).map_err(|errors| HeadlessError::Catalogue(errors.to_string().trim_end().to_string()))
+}
+
+fn names(config: &Game, cells: &[Vec<NounID>]) -> Vec<Vec<String>> {
+    cells.iter()
+        .map(|row| row.iter().map(|&noun_id| config.nouns.nouns[noun_id].name().to_string()).collect())
+        .collect()
+}
+
+fn noun_ids(config: &Game, board: &BoardFile) -> Result<Vec<Vec<NounID>>, HeadlessError> {
+    let width = board.cells.first().map_or(0, Vec::len);
+    if width == 0 || board.cells.iter().any(|row| row.len() != width) {
+        return Err(HeadlessError::RaggedBoard);
+    }
+    if (width, board.cells.len()) != (config.width, config.height) {
+        return Err(HeadlessError::BoardSize {
+            game: board.game.clone(),
+            expected: (config.width, config.height),
+            found: (width, board.cells.len()),
+        });
+    }
+    board.cells.iter()
+        .map(|row| row.iter()
+            .map(|noun| config.nouns.noun_map.get(noun).copied().ok_or_else(|| HeadlessError::UnknownNoun {
+                game: board.game.clone(),
+                noun: noun.clone(),
+            }))
+            .collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::games::puzzle::dsl::{CellContent, Puzzle, PuzzleDefinition};
+
+    const CATALOGUE: &str = "assets/games/default.games.ron";
+
+    fn latin_square(rows: &[&str]) -> BoardFile {
+        let name = |c| match c {
+            '1' => "One",
+            '2' => "Two",
+            '3' => "Three",
+            '4' => "Four",
+            _ => "Blank",
+        };
+        BoardFile {
+            game: "Latin Square".to_string(),
+            seed: None,
+            cells: rows.iter().map(|row| row.chars().map(|c| name(c).to_string()).collect()).collect(),
+        }
+    }
+
+    #[test]
+    fn default_catalogue_checks_out() {
+        assert!(check(Path::new(CATALOGUE)).unwrap() > 0);
+        assert!(Games::load(&[CATALOGUE]).unwrap().names().contains(&"Latin Square"));
+    }
+
+    #[test]
+    fn invalid_catalogues_never_load() {
+        let path = std::env::temp_dir().join(format!("gblpc-invalid-{}.games.ron", std::process::id()));
+        for cells in [
+            r#"(name: "Empty", gen_method: Default), (name: "Mine", gen_method: CountRange(5, 3))"#,
+            r#"(name: "Empty", gen_method: Default), (name: "Empty")"#,
+        ] {
+            std::fs::write(&path, format!(r#"[(name: "Bad", width: 3, height: 3, noun_builder: (spatial_element_builder: (cells: [{cells}])))]"#)).unwrap();
+            assert!(matches!(check(&path), Err(HeadlessError::Catalogue(_))));
+            assert!(matches!(Games::load(&[&path]), Err(HeadlessError::Catalogue(_))));
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn generates_the_same_boards_for_a_seed() {
+        let games = Games::load(&[CATALOGUE]).unwrap();
+        let boards = games.generate("Minesweeper", 7, 3).unwrap();
+        assert_eq!(boards.iter().map(|board| board.seed).collect::<Vec<_>>(), vec![Some(7), Some(8), Some(9)]);
+        assert_eq!(boards, games.generate("Minesweeper", 7, 3).unwrap());
+        assert!(matches!(games.generate("Chess", 7, 1), Err(HeadlessError::UnknownGame(_))));
+    }
+
+    #[test]
+    fn solves_and_rates_with_hints() {
+        let games = Games::load(&[CATALOGUE]).unwrap();
+        let board = latin_square(&["123.", "2.41", "34.2", "4.23"]);
+        let solve = games.solve(&board).unwrap();
+        assert!(solve.solved, "{:?}", solve.stuck);
+        assert_eq!(solve.steps.len(), 4);
+        assert_eq!(solve.board.cells[0][3], "Four");
+        assert_eq!(games.rate(&board).unwrap().difficulty, Difficulty::Easy);
+        // The first blank's row leaves it Three or Four, and its column rules out Four
+        assert_eq!(games.rate(&latin_square(&["12..", "3412", "2143", "4321"])).unwrap().difficulty, Difficulty::Medium);
+        assert_eq!(games.rate(&latin_square(&["....", "....", "....", "...."])).unwrap().difficulty, Difficulty::Fiendish);
+    }
+
+    #[test]
+    fn aquarium_can_be_won() {
+        let games = Games::load(&[CATALOGUE]).unwrap();
+        let board = BoardFile { game: "Aquarium".to_string(), seed: None, cells: vec![vec!["Empty".to_string(); 6]; 6] };
+        let solve = games.solve(&board).unwrap();
+        assert!(solve.solved, "{:?}", solve.stuck);
+        assert_eq!(solve.board.cells[5], vec!["Water"; 6]);
+    }
+
+    #[test]
+    fn linkable_games_rate_like_their_puzzles() {
+        let games = Games::load(&[CATALOGUE]).unwrap();
+        // Givens are the fixed digits, which the puzzle's givens are read from
+        let name = |c| match c {
+            '1' => "Given One",
+            '2' => "Given Two",
+            '3' => "Given Three",
+            '4' => "Given Four",
+            _ => "Blank",
+        };
+        let rows = ["1...", "..3.", ".4..", "...2"];
+        let cells = rows.iter().map(|row| row.chars().map(|c| name(c).to_string()).collect()).collect();
+        let rate = games.rate(&BoardFile { game: "Mini Sudoku".to_string(), seed: None, cells }).unwrap();
+        let mut puzzle = Puzzle::sudoku(4);
+        for (x, y, number) in [(0, 0, 1), (2, 1, 3), (1, 2, 4), (3, 3, 2)] {
+            puzzle.set(x, y, CellContent::Number(number));
+        }
+        let rating = PuzzleDefinition::new(puzzle).rate().unwrap();
+        assert!(rate.solved);
+        assert_eq!(rate.difficulty, rating.difficulty());
+        assert_eq!(rate.steps, rating.techniques.iter().map(|&(_, count)| count).sum::<usize>());
+
+        // The catalogue's `puzz_link` says what a game is, not its name
+        let mut games = games;
+        let renamed = Game { name: "Tiny Sudoku".to_string(), ..games.0["Mini Sudoku"].clone() };
+        games.0.insert(renamed.name.clone(), renamed);
+        let cells = rows.iter().map(|row| row.chars().map(|c| name(c).to_string()).collect()).collect();
+        let renamed_rate = games.rate(&BoardFile { game: "Tiny Sudoku".to_string(), seed: None, cells }).unwrap();
+        assert_eq!(renamed_rate.steps, rate.steps);
+    }
+
+    #[test]
+    fn wrongly_sized_boards_are_errors() {
+        let games = Games::load(&[CATALOGUE]).unwrap();
+        let error = games.solve(&latin_square(&["12", ".."])).unwrap_err();
+        assert!(matches!(error, HeadlessError::BoardSize { expected: (4, 4), found: (2, 2), .. }));
+        assert!(matches!(games.rate(&latin_square(&["123.", "2.41", "34.2"])), Err(HeadlessError::BoardSize { .. })));
+    }
+
+    #[test]
+    fn boards_round_trip_through_both_formats() {
+        let board = latin_square(&["12", ".."]);
+        for format in [Format::Ron, Format::Json] {
+            let source = format.write(&board).unwrap();
+            assert_eq!(format.read::<BoardFile>(&source).unwrap(), board);
+        }
+        assert_eq!(Format::of(Path::new("boards.json")), Format::Json);
+    }
+}
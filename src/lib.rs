@@ -1,23 +1,21 @@
 #![allow(clippy::type_complexity)]
 
-mod actions;
-mod audio;
 mod loading;
 mod menu;
-mod player;
 // pub mod gblpg1;
 // pub mod gblpg;
 // pub mod phind;
 pub mod g;
 pub mod utils;
 pub mod builders;
-// pub mod games;
+pub mod catalogue;
+pub mod save;
+pub mod headless;
+pub mod games;
 
-use crate::actions::ActionsPlugin;
-use crate::audio::InternalAudioPlugin;
+use crate::g::GBLPPlugin;
 use crate::loading::LoadingPlugin;
 use crate::menu::MenuPlugin;
-use crate::player::PlayerPlugin;
 
 use bevy::app::App;
 #[cfg(debug_assertions)]
@@ -32,7 +30,7 @@ enum GameState {
     // During the loading State the LoadingPlugin will load our assets
     #[default]
     Loading,
-    // During this State the actual game logic is executed
+    // During this State the chosen game's board is shown and played
     Playing,
     // Here the menu is drawn and waiting for player interaction
     Menu,
@@ -42,13 +40,9 @@ pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.init_state::<GameState>().add_plugins((
-            LoadingPlugin,
-            MenuPlugin,
-            ActionsPlugin,
-            InternalAudioPlugin,
-            PlayerPlugin,
-        ));
+        app.init_state::<GameState>()
+            .add_plugins((LoadingPlugin, MenuPlugin, GBLPPlugin))
+            .add_systems(Startup, spawn_camera);
 
         #[cfg(debug_assertions)]
         {
@@ -56,3 +50,7 @@ impl Plugin for GamePlugin {
         }
     }
 }
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
@@ -1,15 +1,28 @@
+use crate::g::{GameBoard, GameConfig, GameConfigs, GameSeed};
+use crate::games::puzzle::dsl::PuzzleDefinition;
+use gblpc_core::history::History;
+use gblpc_core::link;
 use crate::loading::TextureAssets;
+use crate::save::SavedGame;
 use crate::GameState;
+use bevy::input::keyboard::{Key, KeyboardInput};
 use bevy::prelude::*;
+use std::time::Duration;
 
 pub struct MenuPlugin;
 
-/// This plugin is responsible for the game menu (containing only one button...)
+/// This plugin is responsible for the game menu, listing every game in the loaded catalogue
 /// The menu is only drawn during the State `GameState::Menu` and is removed when that state is exited
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Menu), setup_menu)
-            .add_systems(Update, click_play_button.run_if(in_state(GameState::Menu)))
+        app.init_resource::<SeedInput>()
+            .add_systems(OnEnter(GameState::Menu), setup_menu)
+            .add_systems(
+                Update,
+                (open_linked_puzzle, list_games, type_seed, click_play_button)
+                    .chain()
+                    .run_if(in_state(GameState::Menu)),
+            )
             .add_systems(OnExit(GameState::Menu), cleanup_menu);
     }
 }
@@ -32,18 +45,35 @@ impl Default for ButtonColors {
 #[derive(Component)]
 struct Menu;
 
-fn setup_menu(mut commands: Commands, textures: Res<TextureAssets>) {
+#[derive(Component)]
+struct GameList;
+
+/// Digits typed in the menu; the next game starts from this seed, or a random one when it's empty
+#[derive(Resource, Default)]
+struct SeedInput(String);
+impl SeedInput {
+    fn label(&self) -> String {
+        if self.0.is_empty() {
+            "Seed : random (type digits to choose one)".to_string()
+        } else {
+            format!("Seed : {}", self.0)
+        }
+    }
+}
+
+#[derive(Component)]
+struct SeedText;
+
+fn setup_menu(mut commands: Commands, textures: Res<TextureAssets>, seed_input: Res<SeedInput>) {
     info!("menu");
-    commands.spawn(Camera2dBundle::default());
     commands
         .spawn((
             NodeBundle {
                 style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    flex_direction: FlexDirection::Column,
-                    align_items: AlignItems::Center,
                     justify_content: JustifyContent::Center,
+                    top: Val::Px(10.),
+                    width: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
                     ..default()
                 },
                 ..default()
@@ -51,34 +81,34 @@ fn setup_menu(mut commands: Commands, textures: Res<TextureAssets>) {
             Menu,
         ))
         .with_children(|children| {
-            let button_colors = ButtonColors::default();
-            children
-                .spawn((
-                    ButtonBundle {
-                        style: Style {
-                            width: Val::Px(140.0),
-                            height: Val::Px(50.0),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            ..Default::default()
-                        },
-                        background_color: button_colors.normal.into(),
-                        ..Default::default()
+            children.spawn((
+                TextBundle::from_section(
+                    seed_input.label(),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: Color::linear_rgb(0.9, 0.9, 0.9),
+                        ..default()
                     },
-                    button_colors,
-                    ChangeState(GameState::Playing),
-                ))
-                .with_children(|parent| {
-                    parent.spawn(TextBundle::from_section(
-                        "Play",
-                        TextStyle {
-                            font_size: 40.0,
-                            color: Color::linear_rgb(0.9, 0.9, 0.9),
-                            ..default()
-                        },
-                    ));
-                });
+                ),
+                SeedText,
+            ));
         });
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
+        },
+        Menu,
+        GameList,
+    ));
     commands
         .spawn((
             NodeBundle {
@@ -176,29 +206,181 @@ fn setup_menu(mut commands: Commands, textures: Res<TextureAssets>) {
 }
 
 #[derive(Component)]
-struct ChangeState(GameState);
+struct ChooseGame(String);
+
+/// Picks the saved game back up
+#[derive(Component)]
+struct ContinueGame(SavedGame);
 
 #[derive(Component)]
 struct OpenLink(&'static str);
 
+// Rebuilds the buttons whenever the catalogue is (re)loaded, since it may arrive after the menu is drawn
+fn list_games(
+    mut commands: Commands,
+    game_configs: Res<GameConfigs>,
+    game_list: Query<(Entity, Ref<GameList>)>,
+) {
+    let Ok((entity, game_list)) = game_list.get_single() else {
+        return;
+    };
+    if !game_list.is_added() && !game_configs.is_changed() {
+        return;
+    }
+    let mut names: Vec<&String> = game_configs.0.keys().collect();
+    names.sort();
+    // A linked game is laid out on its link's size rather than the catalogue's
+    let saved = SavedGame::load().filter(|saved| match &saved.link {
+        Some(link) => linked_game(&game_configs, link).is_ok_and(|game_board| game_board.fits(saved)),
+        None => game_configs.0.get(&saved.name).is_some_and(|config| saved.fits(config)),
+    });
+    commands
+        .entity(entity)
+        .despawn_descendants()
+        .with_children(|children| {
+            if names.is_empty() {
+                children.spawn(TextBundle::from_section(
+                    "Loading games...",
+                    TextStyle {
+                        font_size: 24.0,
+                        color: Color::linear_rgb(0.9, 0.9, 0.9),
+                        ..default()
+                    },
+                ));
+            }
+            if let Some(saved) = saved {
+                let label = format!("Continue {}", saved.name);
+                let button_colors = ButtonColors::default();
+                children
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(280.0),
+                                height: Val::Px(50.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..Default::default()
+                        },
+                        button_colors,
+                        ContinueGame(saved),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            label,
+                            TextStyle {
+                                font_size: 32.0,
+                                color: Color::linear_rgb(0.9, 0.9, 0.9),
+                                ..default()
+                            },
+                        ));
+                    });
+            }
+            for name in names {
+                let button_colors = ButtonColors::default();
+                children
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(280.0),
+                                height: Val::Px(50.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..Default::default()
+                        },
+                        button_colors,
+                        ChooseGame(name.clone()),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            name,
+                            TextStyle {
+                                font_size: 32.0,
+                                color: Color::linear_rgb(0.9, 0.9, 0.9),
+                                ..default()
+                            },
+                        ));
+                    });
+            }
+        });
+}
+
+fn type_seed(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut seed_input: ResMut<SeedInput>,
+    mut seed_text: Query<&mut Text, With<SeedText>>,
+) {
+    for event in keyboard_events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Character(character) if character.chars().all(|c| c.is_ascii_digit()) => {
+                let typed = format!("{}{}", seed_input.0, character);
+                // Ignore digits that would overflow the seed rather than wrapping it
+                if typed.parse::<u64>().is_ok() {
+                    seed_input.0 = typed;
+                }
+            }
+            Key::Backspace => {
+                seed_input.0.pop();
+            }
+            _ => continue,
+        }
+        for mut text in seed_text.iter_mut() {
+            text.sections[0].value = seed_input.label();
+        }
+    }
+}
+
 fn click_play_button(
+    mut commands: Commands,
     mut next_state: ResMut<NextState<GameState>>,
+    game_configs: Res<GameConfigs>,
+    seed_input: Res<SeedInput>,
     mut interaction_query: Query<
         (
             &Interaction,
             &mut BackgroundColor,
             &ButtonColors,
-            Option<&ChangeState>,
+            Option<&ChooseGame>,
+            Option<&ContinueGame>,
             Option<&OpenLink>,
         ),
         (Changed<Interaction>, With<Button>),
     >,
 ) {
-    for (interaction, mut color, button_colors, change_state, open_link) in &mut interaction_query {
+    for (interaction, mut color, button_colors, choose_game, continue_game, open_link) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
-                if let Some(state) = change_state {
-                    next_state.set(state.0.clone());
+                if let Some(config) = choose_game.and_then(|game| game_configs.0.get(&game.0)) {
+                    let game_seed = seed_input.0.parse().map(GameSeed).unwrap_or_else(|_| GameSeed::random());
+                    info!("Starting `{}` with seed {}", config.name, game_seed.0);
+                    commands.insert_resource(game_seed);
+                    commands.insert_resource(GameBoard::new(config.clone()));
+                    next_state.set(GameState::Playing);
+                } else if let Some(ContinueGame(saved)) = continue_game {
+                    let game_board = match &saved.link {
+                        Some(link) => match linked_game(&game_configs, link) {
+                            Ok(game_board) => game_board,
+                            Err(error) => {
+                                warn!("Can't continue `{link}`: {error}");
+                                continue;
+                            },
+                        },
+                        None => {
+                            let Some(config) = game_configs.0.get(&saved.name) else { continue };
+                            GameBoard::new(config.clone())
+                        },
+                    };
+                    commands.insert_resource(GameSeed(saved.seed));
+                    commands.insert_resource(game_board.resuming(saved.clone()));
+                    next_state.set(GameState::Playing);
                 } else if let Some(link) = open_link {
                     if let Err(error) = webbrowser::open(link.0) {
                         warn!("Failed to open link {error:?}");
@@ -215,6 +397,62 @@ fn click_play_button(
     }
 }
 
+// The page's query string on the web
+#[cfg(target_arch = "wasm32")]
+fn linked_puzzle() -> Option<String> {
+    web_sys::window()?.location().search().ok().filter(|search| search.len() > 1)
+}
+
+// Only `--link <url>` or a puzz.link URL on its own, so other arguments aren't read as puzzles
+#[cfg(not(target_arch = "wasm32"))]
+fn linked_puzzle() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--link" { return args.next() }
+        if arg.starts_with("https://puzz.link/") { return Some(arg) }
+    }
+    None
+}
+
+// Opens a puzz.link puzzle the app was started with, once the catalogue has loaded the game that plays it
+fn open_linked_puzzle(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    game_configs: Res<GameConfigs>,
+    mut opened: Local<bool>,
+) {
+    if *opened || game_configs.0.is_empty() { return }
+    *opened = true;
+    let Some(url) = linked_puzzle() else { return };
+    match linked_game(&game_configs, &url) {
+        Ok(game_board) => {
+            commands.insert_resource(GameSeed(0));
+            commands.insert_resource(game_board);
+            next_state.set(GameState::Playing);
+        },
+        Err(error) => warn!("Can't open `{url}`: {error}"),
+    }
+}
+
+// Lays the puzzle's givens out on the catalogue game whose `puzz_link` plays its type, sized to the puzzle
+fn linked_game(game_configs: &GameConfigs, url: &str) -> Result<GameBoard, String> {
+    let definition = PuzzleDefinition::from_url(url).map_err(|error| error.to_string())?;
+    let game = link::game_for(game_configs.0.values().map(|config| &config.game), &definition).map_err(|error| error.to_string())?;
+    let (game, cells) = game.linked(&definition).map_err(|error| error.to_string())?;
+    let config = GameConfig { looks: game_configs.0[&game.name].looks.clone(), game };
+    let saved = SavedGame {
+        name: config.name.clone(),
+        seed: 0,
+        revealed: SavedGame::revealed(&config, &cells),
+        cells,
+        elapsed: Duration::ZERO,
+        hints: 0,
+        history: History::default(),
+        link: Some(url.to_string()),
+    };
+    Ok(GameBoard::new(config).resuming(saved).linked(url))
+}
+
 fn cleanup_menu(mut commands: Commands, menu: Query<Entity, With<Menu>>) {
     for entity in menu.iter() {
         commands.entity(entity).despawn_recursive();
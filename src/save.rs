@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::{Serialize, Deserialize};
+use std::time::Duration;
+use crate::catalogue::ron_options;
+use gblpc_core::game::{Game, NounID};
+use gblpc_core::history::History;
+
+/// A game in progress, kept in a single slot that every game overwrites
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(super) struct SavedGame {
+    pub(super) name: String,
+    pub(super) seed: u64,
+    /// `grid.cells` as `cells[y][x]`
+    pub(super) cells: Vec<Vec<NounID>>,
+    /// Whether each cell's noun is past revealing, which catches a catalogue edit that shifted the noun ids
+    pub(super) revealed: Vec<Vec<bool>>,
+    pub(super) elapsed: Duration,
+    pub(super) hints: usize,
+    pub(super) history: History,
+    /// The puzz.link link a linked game came from, which rebuilds its size, regions and clues on continuing
+    #[serde(default)]
+    pub(super) link: Option<String>,
+}
+impl SavedGame {
+    pub(super) fn revealed(config: &Game, cells: &[Vec<NounID>]) -> Vec<Vec<bool>> {
+        cells.iter()
+            .map(|row| row.iter().map(|&noun_id| config.nouns.nouns[noun_id].reveal().is_none()).collect())
+            .collect()
+    }
+
+    /// Whether the save still lays out on the game as it is now
+    pub(super) fn fits(&self, config: &Game) -> bool {
+        self.name == config.name
+            && self.cells.len() == config.height
+            && self.cells.iter().all(|row| row.len() == config.width)
+            && self.cells.iter().flatten().all(|&noun_id| noun_id < config.nouns.nouns.len())
+            && Self::revealed(config, &self.cells) == self.revealed
+    }
+
+    pub(super) fn load() -> Option<Self> {
+        let source = storage::read()?;
+        ron_options().from_str(&source)
+            .inspect_err(|error| warn!("Ignoring unreadable save: {error}"))
+            .ok()
+    }
+
+    pub(super) fn store(&self) {
+        let stored = ron_options().to_string_pretty(self, PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|source| storage::write(&source));
+        if let Err(error) = stored {
+            warn!("Failed to save `{}`: {error}", self.name);
+        }
+    }
+
+    pub(super) fn clear() {
+        if let Err(error) = storage::remove() {
+            warn!("Failed to clear the save: {error}");
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod storage {
+    use std::path::PathBuf;
+
+    fn path() -> Option<PathBuf> {
+        Some(data_dir()?.join("gblpc").join("save.ron"))
+    }
+
+    #[cfg(target_os = "android")]
+    fn data_dir() -> Option<PathBuf> {
+        bevy::winit::ANDROID_APP.get()?.internal_data_path()
+    }
+
+    #[cfg(not(target_os = "android"))]
+    fn data_dir() -> Option<PathBuf> {
+        let var = |name| std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+        if cfg!(windows) {
+            var("APPDATA")
+        } else if cfg!(target_os = "macos") {
+            var("HOME").map(|home| home.join("Library/Application Support"))
+        } else {
+            var("XDG_DATA_HOME").or_else(|| var("HOME").map(|home| home.join(".local/share")))
+        }
+    }
+
+    pub(super) fn read() -> Option<String> {
+        std::fs::read_to_string(path()?).ok()
+    }
+
+    pub(super) fn write(source: &str) -> Result<(), String> {
+        let path = path().ok_or("no data directory")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+        }
+        std::fs::write(path, source).map_err(|error| error.to_string())
+    }
+
+    pub(super) fn remove() -> Result<(), String> {
+        match path().map(std::fs::remove_file) {
+            Some(Err(error)) if error.kind() != std::io::ErrorKind::NotFound => Err(error.to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod storage {
+    const KEY: &str = "gblpc-save";
+
+    fn local_storage() -> Result<web_sys::Storage, String> {
+        web_sys::window()
+            .ok_or("no window")?
+            .local_storage()
+            .map_err(|error| format!("{error:?}"))?
+            .ok_or_else(|| "no localStorage".to_string())
+    }
+
+    pub(super) fn read() -> Option<String> {
+        local_storage().ok()?.get_item(KEY).ok()?
+    }
+
+    pub(super) fn write(source: &str) -> Result<(), String> {
+        local_storage()?.set_item(KEY, source).map_err(|error| format!("{error:?}"))
+    }
+
+    pub(super) fn remove() -> Result<(), String> {
+        local_storage()?.remove_item(KEY).map_err(|error| format!("{error:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gblpc_core::history::Change;
+
+    #[test]
+    fn round_trips_through_ron() {
+        let mut history = History::default();
+        history.record(Change { x: 1, y: 0, from: 0, to: 2 });
+        history.commit(true);
+        history.record(Change { x: 0, y: 1, from: 0, to: 1 });
+        history.commit(false);
+        history.undo();
+        let saved = SavedGame {
+            name: "Minesweeper".to_string(),
+            seed: 42,
+            cells: vec![vec![0, 2], vec![0, 0]],
+            revealed: vec![vec![false, true], vec![false, false]],
+            elapsed: Duration::from_millis(83_250),
+            hints: 3,
+            history,
+            link: None,
+        };
+        let source = ron_options().to_string_pretty(&saved, PrettyConfig::default()).unwrap();
+        let mut loaded: SavedGame = ron_options().from_str(&source).unwrap();
+        assert_eq!(loaded, saved);
+        assert_eq!(loaded.history.redo().unwrap().changes, vec![Change { x: 0, y: 1, from: 0, to: 1 }]);
+    }
+}
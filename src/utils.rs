@@ -1,46 +1,5 @@
 use bevy::prelude::*;
 use bevy::color::palettes::css::*;
-use ron::{self, extensions::Extensions, options::Options};
-use std::io::{BufReader, Read};
-use std::fs::File;
-use crate::g::GameConfigs;
-use crate::builders::GameConfigBuilder;
-
-pub(super) fn parse_games(
-    asset_server: Res<AssetServer>,
-    mut game_configs: ResMut<GameConfigs>,
-) {
-    if let Ok(game_config_builders) = read_games("src/games.ron") {
-        for game_config_builder in game_config_builders {
-            let game_config = game_config_builder.to_game_config(asset_server.clone());
-            game_configs.0.insert(game_config.name.clone(), game_config);
-        }
-    } else {
-        panic!("Failed to Parse Game!");
-    }
-}
-
-fn read_games(file_path: &str) -> std::io::Result<Vec<GameConfigBuilder>> {
-    // Setup the options
-    let options = Options::default()
-        .without_default_extension(Extensions::EXPLICIT_STRUCT_NAMES)
-        .with_default_extension(Extensions::IMPLICIT_SOME);
-
-    let mut contents = String::new();
-    read_ron(&mut contents, file_path);
-    let game_config_builders: Vec<GameConfigBuilder> = options.from_str(&mut contents).unwrap();
-
-    Ok(game_config_builders)
-}
-
-fn read_ron(contents: &mut String, file_path: &str) -> std::io::Result<()> {
-    let file = File::open(file_path)?;
-    let mut buf_reader = BufReader::new(file);
-    buf_reader.read_to_string(contents)?;
-    Ok(())
-}
-
-
 
 pub(super) fn spawn_header(builder: &mut ChildBuilder, font: &Handle<Font>, game_title: &str) {
     // Header
@@ -60,7 +19,11 @@ pub(super) fn spawn_header(builder: &mut ChildBuilder, font: &Handle<Font>, game
         });
 }
 
-pub(super) fn spawn_right_side_bar(builder: &mut ChildBuilder, font: &Handle<Font>) {
+/// The side bar's headline, which games rewrite as they're played and won
+#[derive(Component)]
+pub(super) struct GameStateText;
+
+pub(super) fn spawn_right_side_bar(builder: &mut ChildBuilder, font: &Handle<Font>, spawn_children: impl FnOnce(&mut ChildBuilder)) {
     // Right side bar (auto placed in row 2, column 2)
     builder
         .spawn(NodeBundle {
@@ -72,9 +35,9 @@ pub(super) fn spawn_right_side_bar(builder: &mut ChildBuilder, font: &Handle<Fon
                 justify_items: JustifyItems::Center,
                 // Add 10px padding
                 padding: UiRect::all(Val::Px(10.)),
-                // Add an fr track to take up all the available space at the bottom of the column so that the text nodes
-                // can be top-aligned. Normally you'd use flexbox for this, but this is the CSS Grid example so we're using grid.
-                grid_template_rows: vec![GridTrack::auto(), GridTrack::auto(), GridTrack::fr(1.0)],
+                // Size every row to its content and pack the rows at the top, since games add their own rows to the side bar
+                grid_auto_rows: vec![GridTrack::auto()],
+                align_content: AlignContent::Start,
                 // Add a 10px gap between rows
                 row_gap: Val::Px(10.),
                 ..default()
@@ -83,14 +46,14 @@ pub(super) fn spawn_right_side_bar(builder: &mut ChildBuilder, font: &Handle<Fon
             ..default()
         })
         .with_children(|builder| {
-            builder.spawn(TextBundle::from_section(
+            builder.spawn((GameStateText, TextBundle::from_section(
                 "Game State",
                 TextStyle {
                     font: font.clone(),
                     font_size: 24.0,
                     ..default()
                 },
-            ));
+            )));
             builder.spawn(TextBundle::from_section(
                 "Revealed : 0 / 0",
                 TextStyle {
@@ -99,10 +62,38 @@ pub(super) fn spawn_right_side_bar(builder: &mut ChildBuilder, font: &Handle<Fon
                     ..default()
                 },
             ));
+            spawn_children(builder);
             builder.spawn(NodeBundle::default());
         });
 }
 
+pub(super) fn spawn_button(builder: &mut ChildBuilder, font: &Handle<Font>, label: &str, marker: impl Component) {
+    builder
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::linear_rgb(0.15, 0.15, 0.15)),
+                ..default()
+            },
+            marker,
+        ))
+        .with_children(|builder| {
+            builder.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 16.0,
+                    color: Color::linear_rgb(0.9, 0.9, 0.9),
+                },
+            ));
+        });
+}
+
 pub(super) fn spawn_footer(builder: &mut ChildBuilder) {
     // Footer / status bar
     builder.spawn(NodeBundle {
@@ -132,7 +123,11 @@ pub(super) fn spawn_nested_text_bundle(builder: &mut ChildBuilder, font: Handle<
 
 
 pub(super) fn str_to_css_srgba(color_str: &str) -> Srgba {
-    match color_str {
+    try_str_to_css_srgba(color_str).unwrap_or(WHITE)
+}
+
+pub(super) fn try_str_to_css_srgba(color_str: &str) -> Option<Srgba> {
+    let color = match color_str {
         "ALICE_BLUE" => ALICE_BLUE,
         "ANTIQUE_WHITE" => ANTIQUE_WHITE,
         "AQUA" => AQUA,
@@ -280,6 +275,7 @@ pub(super) fn str_to_css_srgba(color_str: &str) -> Srgba {
         "WHITE_SMOKE" => WHITE_SMOKE,
         "YELLOW" => YELLOW,
         "YELLOW_GREEN" => YELLOW_GREEN,
-        _ => WHITE,
-    }
+        _ => return None,
+    };
+    Some(color)
 }
\ No newline at end of file